@@ -0,0 +1,198 @@
+use serde::Deserialize;
+
+use super::{BlockFace, BlockStateId, Tool};
+use crate::{coords::BlockPos, engine::model::RenderClass, world::World};
+
+/// Gameplay hooks of a block. Every hook has a no-op default, so behaviours
+/// only implement the events they care about. Hooks get the world itself and
+/// may read or edit any loaded block.
+#[allow(unused)]
+pub trait BlockBehavior: Sync {
+    /// Called after the block was placed at `pos`.
+    fn on_placed(&self, world: &mut World, pos: BlockPos, state: BlockStateId) {}
+
+    /// Called before the block at `pos` is replaced with air.
+    fn on_broken(&self, world: &mut World, pos: BlockPos, state: BlockStateId) {}
+
+    /// Called when the block at `from`, next to `pos`, changed.
+    fn on_neighbor_changed(
+        &self,
+        world: &mut World,
+        pos: BlockPos,
+        state: BlockStateId,
+        from: BlockPos,
+    ) {
+    }
+
+    /// Called when a tick requested with [`World::schedule_tick`] is due.
+    fn on_scheduled_tick(&self, world: &mut World, pos: BlockPos, state: BlockStateId) {}
+
+    /// Called for blocks picked at random every world tick.
+    fn on_random_tick(&self, world: &mut World, pos: BlockPos, state: BlockStateId) {}
+
+    /// Called when the block is used. Returns whether the use was handled.
+    fn on_use(&self, world: &mut World, pos: BlockPos, state: BlockStateId) -> bool {
+        false
+    }
+}
+
+/// The behaviour a block definition refers to by name.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Behavior {
+    #[default]
+    None,
+    /// Falls down while the block below is not solid, like sand.
+    Falling,
+    /// Pops off when the block below stops supporting it, like plants and torches.
+    Attached,
+    /// Attached, and grows through its `age` property on random ticks.
+    Crop,
+    /// Turns into its `decays_to` block when covered by an opaque block.
+    Grass,
+}
+
+impl Behavior {
+    pub fn get(&self) -> &'static dyn BlockBehavior {
+        match self {
+            Behavior::None => &NoBehavior,
+            Behavior::Falling => &Falling,
+            Behavior::Attached => &Attached,
+            Behavior::Crop => &Crop,
+            Behavior::Grass => &Grass,
+        }
+    }
+}
+
+struct NoBehavior;
+
+impl BlockBehavior for NoBehavior {}
+
+/// Ticks between a falling block losing support and it moving down.
+const FALL_DELAY: u64 = 2;
+
+struct Falling;
+
+impl Falling {
+    fn can_fall_into(world: &World, pos: BlockPos) -> bool {
+        world
+            .get_block(pos)
+            .is_ok_and(|below| !world.registry().block_of(below).solid)
+    }
+}
+
+impl BlockBehavior for Falling {
+    fn on_placed(&self, world: &mut World, pos: BlockPos, _state: BlockStateId) {
+        world.schedule_tick(pos, FALL_DELAY);
+    }
+
+    fn on_neighbor_changed(
+        &self,
+        world: &mut World,
+        pos: BlockPos,
+        _state: BlockStateId,
+        _from: BlockPos,
+    ) {
+        world.schedule_tick(pos, FALL_DELAY);
+    }
+
+    fn on_scheduled_tick(&self, world: &mut World, pos: BlockPos, state: BlockStateId) {
+        let below = pos.below();
+        if Self::can_fall_into(world, below) && world.replace_block(pos, BlockStateId::AIR).is_ok()
+        {
+            // The block below was just read, so its chunk is loaded.
+            world.place_block(below, state).ok();
+        }
+    }
+}
+
+struct Attached;
+
+impl Attached {
+    fn is_supported(world: &World, pos: BlockPos) -> bool {
+        world.get_block(pos.below()).is_ok_and(|below| {
+            let registry = world.registry();
+            registry.block_of(below).solid && registry.state(below).model().covers(BlockFace::Top)
+        })
+    }
+}
+
+impl BlockBehavior for Attached {
+    fn on_placed(&self, world: &mut World, pos: BlockPos, _state: BlockStateId) {
+        if !Self::is_supported(world, pos) {
+            world.break_block(pos, &Tool::HAND).ok();
+        }
+    }
+
+    fn on_neighbor_changed(
+        &self,
+        world: &mut World,
+        pos: BlockPos,
+        _state: BlockStateId,
+        from: BlockPos,
+    ) {
+        if from == pos.below() && !Self::is_supported(world, pos) {
+            world.break_block(pos, &Tool::HAND).ok();
+        }
+    }
+}
+
+/// Chance for a crop to advance one growth stage on a random tick.
+const CROP_GROWTH_CHANCE: f64 = 0.25;
+
+struct Crop;
+
+impl BlockBehavior for Crop {
+    fn on_placed(&self, world: &mut World, pos: BlockPos, state: BlockStateId) {
+        Attached.on_placed(world, pos, state);
+    }
+
+    fn on_neighbor_changed(
+        &self,
+        world: &mut World,
+        pos: BlockPos,
+        state: BlockStateId,
+        from: BlockPos,
+    ) {
+        Attached.on_neighbor_changed(world, pos, state, from);
+    }
+
+    fn on_random_tick(&self, world: &mut World, pos: BlockPos, state: BlockStateId) {
+        if !rand::random_bool(CROP_GROWTH_CHANCE) {
+            return;
+        }
+
+        let registry = world.registry();
+        let Some(age) = registry.property(state, "age") else {
+            return;
+        };
+        let ages = &registry.block_of(state).properties;
+        let next_age = ages
+            .iter()
+            .find(|property| property.name == "age")
+            .and_then(|property| {
+                let index = property.values.iter().position(|value| value == age)?;
+                property.values.get(index + 1)
+            });
+
+        if let Some(grown) = next_age.and_then(|age| registry.with_property(state, "age", age)) {
+            world.replace_block(pos, grown).ok();
+        }
+    }
+}
+
+struct Grass;
+
+impl BlockBehavior for Grass {
+    fn on_random_tick(&self, world: &mut World, pos: BlockPos, state: BlockStateId) {
+        let Ok(above) = world.get_block(pos.above()) else {
+            return;
+        };
+
+        let registry = world.registry();
+        let covered = registry.block_of(above).render_class == RenderClass::Opaque
+            && registry.state(above).model().covers(BlockFace::Bottom);
+        if covered && let Some(decayed) = registry.block_of(state).decay_state() {
+            world.replace_block(pos, decayed).ok();
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::anyhow;
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{Block, BlockRegistry, BlockStateId};
+
+/// The kind of tool a block is best mined with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ToolClass {
+    Pickaxe,
+    Shovel,
+    Axe,
+    Hoe,
+    Shears,
+}
+
+/// The tool a block is broken with.
+#[derive(Debug, Clone, Copy)]
+pub struct Tool {
+    pub class: Option<ToolClass>,
+    /// Mining speed multiplier, applied when the class matches the block's tool.
+    pub speed: f32,
+    pub silk_touch: bool,
+}
+
+impl Tool {
+    pub const HAND: Tool = Tool {
+        class: None,
+        speed: 1.0,
+        silk_touch: false,
+    };
+}
+
+fn default_count() -> (u32, u32) {
+    (1, 1)
+}
+
+fn default_probability() -> f64 {
+    1.0
+}
+
+/// One entry of a drop table.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Drop {
+    pub item: String,
+    /// Inclusive range the dropped count is rolled from.
+    #[serde(default = "default_count")]
+    pub count: (u32, u32),
+    /// Chance of this entry dropping at all.
+    #[serde(default = "default_probability")]
+    pub probability: f64,
+    /// Item of another entry that does not drop when this one does.
+    #[serde(default)]
+    pub replaces: Option<String>,
+}
+
+/// What a block drops. Without `items` the block drops itself; `silk_touch`
+/// replaces the drops when mined with a silk touch tool.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct DropTable {
+    pub items: Option<Vec<Drop>>,
+    pub silk_touch: Option<Vec<Drop>>,
+}
+
+impl DropTable {
+    /// Checks that every entry can be rolled: a named item, a count range with
+    /// its minimum first, a probability between 0 and 1, and `replaces` naming
+    /// another entry of the same list.
+    pub(super) fn validate(&self, block: &str) -> anyhow::Result<()> {
+        for drops in [&self.items, &self.silk_touch].into_iter().flatten() {
+            for drop in drops {
+                if drop.item.is_empty() {
+                    return Err(anyhow!("A drop of {} has no item", block));
+                }
+                if drop.count.0 > drop.count.1 {
+                    return Err(anyhow!(
+                        "Drop {} of {} has count {:?} with its minimum above its maximum",
+                        drop.item,
+                        block,
+                        drop.count
+                    ));
+                }
+                if !(0.0..=1.0).contains(&drop.probability) {
+                    return Err(anyhow!(
+                        "Drop {} of {} has probability {}, which is not between 0 and 1",
+                        drop.item,
+                        block,
+                        drop.probability
+                    ));
+                }
+                if let Some(replaced) = &drop.replaces
+                    && !drops
+                        .iter()
+                        .any(|other| other.item == *replaced && other.item != drop.item)
+                {
+                    return Err(anyhow!(
+                        "Drop {} of {} replaces {}, which is not another drop",
+                        drop.item,
+                        block,
+                        replaced
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A rolled drop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: String,
+    pub count: u32,
+}
+
+impl Block {
+    /// Whether breaking the block with `tool` yields its drops.
+    pub fn can_harvest(&self, tool: &Tool) -> bool {
+        !self.requires_tool || (tool.class.is_some() && tool.class == self.tool)
+    }
+
+    /// Seconds it takes to break the block with `tool`, or `None` if it cannot be broken.
+    pub fn break_time(&self, tool: &Tool) -> Option<f32> {
+        if self.hardness < 0.0 {
+            return None;
+        }
+
+        let speed = if tool.class.is_some() && tool.class == self.tool {
+            tool.speed
+        } else {
+            1.0
+        };
+        let penalty = if self.can_harvest(tool) { 1.5 } else { 5.0 };
+
+        Some(self.hardness * penalty / speed)
+    }
+
+    /// Rolls the drops of breaking the block with `tool`.
+    pub fn roll_drops(&self, tool: &Tool, rng: &mut impl Rng) -> Vec<ItemStack> {
+        if !self.can_harvest(tool) {
+            return Vec::new();
+        }
+
+        let table = match (&self.drops.silk_touch, &self.drops.items) {
+            (Some(silk_touch), _) if tool.silk_touch => silk_touch,
+            (_, Some(items)) => items,
+            _ => {
+                return vec![ItemStack {
+                    item: self.name.clone(),
+                    count: 1,
+                }];
+            }
+        };
+
+        let dropped: Vec<&Drop> = table
+            .iter()
+            .filter(|drop| rng.random_bool(drop.probability))
+            .collect();
+
+        dropped
+            .iter()
+            .filter(|drop| {
+                !dropped
+                    .iter()
+                    .any(|other| other.replaces.as_ref() == Some(&drop.item))
+            })
+            .filter_map(|drop| {
+                let count = rng.random_range(drop.count.0..=drop.count.1);
+                (count > 0).then(|| ItemStack {
+                    item: drop.item.clone(),
+                    count,
+                })
+            })
+            .collect()
+    }
+}
+
+impl BlockRegistry {
+    /// Seconds it takes to break `state` with `tool`, or `None` if it cannot be broken.
+    pub fn break_time(&self, state: BlockStateId, tool: &Tool) -> Option<f32> {
+        self.block_of(state).break_time(tool)
+    }
+
+    /// Rolls the drops of breaking `state` with `tool`, or `None` if it cannot be broken.
+    pub fn mine(
+        &self,
+        state: BlockStateId,
+        tool: &Tool,
+        rng: &mut impl Rng,
+    ) -> Option<Vec<ItemStack>> {
+        self.break_time(state, tool)?;
+        Some(self.block_of(state).roll_drops(tool, rng))
+    }
+
+    /// Whether `state` withstands an explosion that reaches it with `power`.
+    /// Unbreakable blocks always do.
+    pub fn resists_explosion(&self, state: BlockStateId, power: f32) -> bool {
+        let block = self.block_of(state);
+        block.hardness < 0.0 || block.blast_resistance >= power
+    }
+}
@@ -0,0 +1,250 @@
+mod behavior;
+mod mining;
+mod registry;
+mod shape;
+mod state;
+mod textures;
+
+use cgmath::{Point3, Vector3};
+use serde::Deserialize;
+
+use crate::engine::model::RenderClass;
+
+pub use behavior::Behavior;
+pub use mining::{DropTable, ItemStack, Tool, ToolClass};
+pub use registry::BlockRegistry;
+pub use shape::{Shape, ShapeModel, cross_quads};
+pub use state::{BlockState, BlockStateId, Property, Rotation};
+pub use textures::{BlockTextures, TextureIndices};
+
+#[derive(Debug, Clone, Copy)]
+pub enum BlockFace {
+    Front,
+    Back,
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl BlockFace {
+    pub const ALL: [BlockFace; 6] = [
+        BlockFace::Front,
+        BlockFace::Back,
+        BlockFace::Left,
+        BlockFace::Right,
+        BlockFace::Top,
+        BlockFace::Bottom,
+    ];
+
+    pub fn get_normal(&self) -> Point3<f32> {
+        match self {
+            BlockFace::Front => Point3::new(0.0, 0.0, 1.0),
+            BlockFace::Back => Point3::new(0.0, 0.0, -1.0),
+            BlockFace::Left => Point3::new(-1.0, 0.0, 0.0),
+            BlockFace::Right => Point3::new(1.0, 0.0, 0.0),
+            BlockFace::Top => Point3::new(0.0, 1.0, 0.0),
+            BlockFace::Bottom => Point3::new(0.0, -1.0, 0.0),
+        }
+    }
+
+    pub fn from_normal(normal: Vector3<f32>) -> Option<BlockFace> {
+        BlockFace::ALL
+            .into_iter()
+            .find(|face| face.get_normal() == Point3::new(normal.x, normal.y, normal.z))
+    }
+
+    /// Integer step from a block to its neighbour through this face.
+    pub fn offset(&self) -> [i32; 3] {
+        match self {
+            BlockFace::Front => [0, 0, 1],
+            BlockFace::Back => [0, 0, -1],
+            BlockFace::Left => [-1, 0, 0],
+            BlockFace::Right => [1, 0, 0],
+            BlockFace::Top => [0, 1, 0],
+            BlockFace::Bottom => [0, -1, 0],
+        }
+    }
+
+    pub fn opposite(&self) -> BlockFace {
+        match self {
+            BlockFace::Front => BlockFace::Back,
+            BlockFace::Back => BlockFace::Front,
+            BlockFace::Left => BlockFace::Right,
+            BlockFace::Right => BlockFace::Left,
+            BlockFace::Top => BlockFace::Bottom,
+            BlockFace::Bottom => BlockFace::Top,
+        }
+    }
+
+    pub fn get_vertices(&self, position: Point3<f32>) -> [Point3<f32>; 4] {
+        self.get_box_vertices(position, position + Vector3::new(1.0, 1.0, 1.0))
+    }
+
+    /// Corners of this face of the box spanning `min` to `max`.
+    pub fn get_box_vertices(&self, min: Point3<f32>, max: Point3<f32>) -> [Point3<f32>; 4] {
+        let (x0, y0, z0) = (min.x, min.y, min.z);
+        let (x1, y1, z1) = (max.x, max.y, max.z);
+
+        match self {
+            BlockFace::Front => [
+                Point3::new(x0, y0, z1),
+                Point3::new(x1, y0, z1),
+                Point3::new(x1, y1, z1),
+                Point3::new(x0, y1, z1),
+            ],
+            BlockFace::Back => [
+                Point3::new(x1, y0, z0),
+                Point3::new(x0, y0, z0),
+                Point3::new(x0, y1, z0),
+                Point3::new(x1, y1, z0),
+            ],
+            BlockFace::Left => [
+                Point3::new(x0, y0, z0),
+                Point3::new(x0, y0, z1),
+                Point3::new(x0, y1, z1),
+                Point3::new(x0, y1, z0),
+            ],
+            BlockFace::Right => [
+                Point3::new(x1, y0, z1),
+                Point3::new(x1, y0, z0),
+                Point3::new(x1, y1, z0),
+                Point3::new(x1, y1, z1),
+            ],
+            BlockFace::Top => [
+                Point3::new(x0, y1, z1),
+                Point3::new(x1, y1, z1),
+                Point3::new(x1, y1, z0),
+                Point3::new(x0, y1, z0),
+            ],
+            BlockFace::Bottom => [
+                Point3::new(x0, y0, z0),
+                Point3::new(x1, y0, z0),
+                Point3::new(x1, y0, z1),
+                Point3::new(x0, y0, z1),
+            ],
+        }
+    }
+}
+
+/// Numeric id of a block definition.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(transparent)]
+pub struct BlockId(pub u16);
+
+/// Texture names for each face of a block. More specific entries win:
+/// `front`/`back`/`left`/`right` over `side`, and any face over `all`.
+/// Names may contain `{property}` placeholders, filled in per block state.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct FaceTextures {
+    pub all: Option<String>,
+    pub side: Option<String>,
+    pub top: Option<String>,
+    pub bottom: Option<String>,
+    pub front: Option<String>,
+    pub back: Option<String>,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+impl FaceTextures {
+    pub fn get(&self, face: BlockFace) -> Option<&str> {
+        let specific = match face {
+            BlockFace::Front => &self.front,
+            BlockFace::Back => &self.back,
+            BlockFace::Left => &self.left,
+            BlockFace::Right => &self.right,
+            BlockFace::Top => &self.top,
+            BlockFace::Bottom => &self.bottom,
+        };
+        let side = match face {
+            BlockFace::Top | BlockFace::Bottom => &None,
+            _ => &self.side,
+        };
+
+        specific
+            .as_ref()
+            .or(side.as_ref())
+            .or(self.all.as_ref())
+            .map(String::as_str)
+    }
+}
+
+fn default_solid() -> bool {
+    true
+}
+
+/// A block definition, as loaded from `res/blocks.ron`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Block {
+    pub name: String,
+    pub id: BlockId,
+    #[serde(default)]
+    pub textures: FaceTextures,
+    #[serde(default)]
+    pub render_class: RenderClass,
+    #[serde(default = "default_solid")]
+    pub solid: bool,
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub light_emission: u8,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+    #[serde(default)]
+    pub shape: Shape,
+    #[serde(default)]
+    pub behavior: Behavior,
+    /// Base break time factor; negative for unbreakable blocks.
+    #[serde(default)]
+    pub hardness: f32,
+    /// Explosion power the block withstands.
+    #[serde(default)]
+    pub blast_resistance: f32,
+    /// The tool class that mines this block fastest.
+    #[serde(default)]
+    pub tool: Option<ToolClass>,
+    /// Whether the block only drops when mined with its tool.
+    #[serde(default)]
+    pub requires_tool: bool,
+    #[serde(default)]
+    pub drops: DropTable,
+    /// Block this one turns into when its behaviour decays it, such as covered
+    /// grass into dirt.
+    #[serde(default)]
+    pub decays_to: Option<String>,
+
+    #[serde(skip)]
+    first_state: BlockStateId,
+    /// Default state of `decays_to`, resolved by the registry.
+    #[serde(skip)]
+    decay_state: Option<BlockStateId>,
+}
+
+impl Block {
+    pub fn get_texture_index(&self, state: &BlockState, face: BlockFace) -> u32 {
+        debug_assert_eq!(state.block, self.id);
+
+        state.texture_indices[state.rotation.model_face(face) as usize]
+    }
+
+    /// The state with the first value of every property.
+    pub fn default_state(&self) -> BlockStateId {
+        self.first_state
+    }
+
+    /// The state this block decays into, see [`Block::decays_to`].
+    pub fn decay_state(&self) -> Option<BlockStateId> {
+        self.decay_state
+    }
+
+    /// Whether this block, as a neighbour, hides the face of `other` that touches it.
+    /// Shapes are checked separately; this only covers the render class rules.
+    pub fn hides_face_of(&self, other: &Block) -> bool {
+        match self.render_class {
+            RenderClass::Opaque => true,
+            RenderClass::Cutout => false,
+            RenderClass::Translucent => self.id == other.id,
+        }
+    }
+}
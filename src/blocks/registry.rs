@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use anyhow::{Ok, anyhow};
+
+use super::{Block, BlockFace, BlockId, BlockState, BlockStateId, Rotation, ShapeModel};
+use crate::engine::{model::RenderClass, resources::load_string};
+
+pub struct BlockRegistry {
+    blocks: Vec<Block>,
+    states: Vec<BlockState>,
+    by_name: HashMap<String, BlockId>,
+    texture_names: Vec<String>,
+}
+
+impl BlockRegistry {
+    /// Loads block definitions from a RON file in `res/`.
+    pub fn load(file_name: &str) -> anyhow::Result<Self> {
+        let source = load_string(file_name)?;
+        let definitions: Vec<Block> = ron::from_str(&source)
+            .map_err(|e| anyhow!("Failed to parse block definitions {}: {}", file_name, e))?;
+
+        Self::from_definitions(definitions)
+    }
+
+    pub fn from_definitions(mut definitions: Vec<Block>) -> anyhow::Result<Self> {
+        definitions.sort_by_key(|block| block.id.0);
+
+        for (expected, block) in definitions.iter().enumerate() {
+            if block.id.0 as usize != expected {
+                return Err(anyhow!(
+                    "Block ids must be contiguous from 0, but {} has id {} (expected {})",
+                    block.name,
+                    block.id.0,
+                    expected
+                ));
+            }
+        }
+
+        match definitions.first() {
+            Some(air) if air.name == "air" && air.properties.is_empty() => {}
+            _ => return Err(anyhow!("Block id 0 must be reserved for \"air\"")),
+        }
+
+        let mut registry = Self {
+            blocks: Vec::new(),
+            states: Vec::new(),
+            by_name: HashMap::new(),
+            texture_names: Vec::new(),
+        };
+
+        for mut block in definitions {
+            if registry.by_name.contains_key(&block.name) {
+                return Err(anyhow!("Duplicate block name {}", block.name));
+            }
+
+            block.drops.validate(&block.name)?;
+            registry.add_states(&mut block)?;
+            registry.by_name.insert(block.name.clone(), block.id);
+            registry.blocks.push(block);
+        }
+
+        for index in 0..registry.blocks.len() {
+            let block = &registry.blocks[index];
+            let Some(target) = &block.decays_to else {
+                continue;
+            };
+            let state = registry
+                .get_by_name(target)
+                .map_err(|_| anyhow!("{} decays to unknown block {}", block.name, target))?
+                .default_state();
+            registry.blocks[index].decay_state = Some(state);
+        }
+
+        Ok(registry)
+    }
+
+    fn add_states(&mut self, block: &mut Block) -> anyhow::Result<()> {
+        let mut state_count = 1usize;
+        for property in &block.properties {
+            if property.values.is_empty() || property.values.len() > u8::MAX as usize {
+                return Err(anyhow!(
+                    "Property {} of {} must have between 1 and {} values",
+                    property.name,
+                    block.name,
+                    u8::MAX
+                ));
+            }
+            state_count = state_count
+                .checked_mul(property.values.len())
+                .ok_or_else(|| too_many_states(block))?;
+        }
+
+        if self.states.len() + state_count > u16::MAX as usize {
+            return Err(too_many_states(block));
+        }
+
+        block.first_state = BlockStateId(self.states.len() as u16);
+
+        for index in 0..state_count {
+            let mut values = vec![0u8; block.properties.len()];
+            let mut rest = index;
+            for (slot, property) in block.properties.iter().enumerate().rev() {
+                values[slot] = (rest % property.values.len()) as u8;
+                rest /= property.values.len();
+            }
+
+            let mut rotation = Rotation::default();
+            for (property, &value) in block.properties.iter().zip(&values) {
+                if let Some(property_rotation) =
+                    Rotation::from_property(&property.name, &property.values[value as usize])
+                {
+                    rotation = property_rotation;
+                }
+            }
+
+            let model = ShapeModel::new(block.shape, &rotation, |name| {
+                block
+                    .properties
+                    .iter()
+                    .zip(&values)
+                    .find(|(property, _)| property.name == name)
+                    .map(|(property, &value)| property.values[value as usize].as_str())
+            });
+
+            let mut texture_indices = [0; 6];
+            for face in BlockFace::ALL {
+                let Some(template) = block.textures.get(face) else {
+                    continue;
+                };
+
+                let mut texture = template.to_owned();
+                for (property, &value) in block.properties.iter().zip(&values) {
+                    texture = texture.replace(
+                        &format!("{{{}}}", property.name),
+                        &property.values[value as usize],
+                    );
+                }
+                texture_indices[face as usize] = self.texture_index(texture);
+            }
+
+            self.states.push(BlockState {
+                block: block.id,
+                values,
+                rotation,
+                model,
+                texture_indices,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn texture_index(&mut self, texture: String) -> u32 {
+        match self.texture_names.iter().position(|name| *name == texture) {
+            Some(index) => index as u32,
+            None => {
+                self.texture_names.push(texture);
+                self.texture_names.len() as u32 - 1
+            }
+        }
+    }
+
+    pub fn get(&self, id: BlockId) -> &Block {
+        &self.blocks[id.0 as usize]
+    }
+
+    pub fn get_by_name(&self, name: &str) -> anyhow::Result<&Block> {
+        self.by_name
+            .get(name)
+            .map(|id| self.get(*id))
+            .ok_or_else(|| anyhow!("Unknown block {}", name))
+    }
+
+    /// Number of block states; valid ids are below it.
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn state(&self, id: BlockStateId) -> &BlockState {
+        &self.states[id.0 as usize]
+    }
+
+    pub fn block_of(&self, id: BlockStateId) -> &Block {
+        self.get(self.state(id).block)
+    }
+
+    /// Whether the state is a full cube that hides everything behind it.
+    pub fn is_opaque_cube(&self, id: BlockStateId) -> bool {
+        !id.is_air()
+            && self.block_of(id).render_class == RenderClass::Opaque
+            && self.state(id).model().is_full()
+    }
+
+    /// The value `property` takes in the given state, if the block has that property.
+    pub fn property(&self, id: BlockStateId, property: &str) -> Option<&str> {
+        let state = self.state(id);
+        let block = self.get(state.block);
+        let slot = block.properties.iter().position(|p| p.name == property)?;
+
+        Some(&block.properties[slot].values[state.values[slot] as usize])
+    }
+
+    /// The state that differs from `id` only in `property` being set to `value`.
+    pub fn with_property(
+        &self,
+        id: BlockStateId,
+        property: &str,
+        value: &str,
+    ) -> Option<BlockStateId> {
+        let state = self.state(id);
+        let block = self.get(state.block);
+        let slot = block.properties.iter().position(|p| p.name == property)?;
+        let new_value = block.properties[slot]
+            .values
+            .iter()
+            .position(|v| v == value)?;
+
+        let index = block
+            .properties
+            .iter()
+            .enumerate()
+            .fold(0usize, |index, (i, property)| {
+                let value = if i == slot {
+                    new_value
+                } else {
+                    state.values[i] as usize
+                };
+                index * property.values.len() + value
+            });
+
+        Some(BlockStateId(block.first_state.0 + index as u16))
+    }
+
+    /// Names of every texture referenced by a block state, in texture index order.
+    pub fn texture_names(&self) -> &[String] {
+        &self.texture_names
+    }
+}
+
+fn too_many_states(block: &Block) -> anyhow::Error {
+    anyhow!("Too many block states, {} does not fit", block.name)
+}
@@ -0,0 +1,214 @@
+use cgmath::Point3;
+use serde::Deserialize;
+
+use super::{BlockFace, Rotation};
+
+/// The geometry a block is drawn with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Shape {
+    #[default]
+    Cube,
+    /// Half block, picked by the `type` property: `bottom`, `top` or `double`.
+    Slab,
+    /// Oriented by `facing`, and flipped upside down when `half` is `top`.
+    Stairs,
+    /// Post with rails towards connecting neighbours.
+    Fence,
+    /// Thin full-height post with panes towards connecting neighbours.
+    Pane,
+    /// Two crossed quads, used by plants.
+    Cross,
+}
+
+/// Axis-aligned box in block-local space, from 0.0 to 1.0 on every axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    pub const FULL: Aabb = Aabb {
+        min: Point3::new(0.0, 0.0, 0.0),
+        max: Point3::new(1.0, 1.0, 1.0),
+    };
+
+    /// A box given in sixteenths of a block, the unit textures are drawn in.
+    pub fn pixels(min: [u8; 3], max: [u8; 3]) -> Self {
+        let to_block = |v: [u8; 3]| Point3::new(v[0] as f32, v[1] as f32, v[2] as f32) / 16.0;
+        Self {
+            min: to_block(min),
+            max: to_block(max),
+        }
+    }
+
+    /// Whether the given face of this box lies on the boundary of the block.
+    pub fn touches(&self, face: BlockFace) -> bool {
+        match face {
+            BlockFace::Front => self.max.z >= 1.0,
+            BlockFace::Back => self.min.z <= 0.0,
+            BlockFace::Left => self.min.x <= 0.0,
+            BlockFace::Right => self.max.x >= 1.0,
+            BlockFace::Top => self.max.y >= 1.0,
+            BlockFace::Bottom => self.min.y <= 0.0,
+        }
+    }
+
+    fn contains_on_face(&self, face: BlockFace, u: f32, v: f32) -> bool {
+        let (u_min, u_max, v_min, v_max) = match face {
+            BlockFace::Front | BlockFace::Back => (self.min.x, self.max.x, self.min.y, self.max.y),
+            BlockFace::Left | BlockFace::Right => (self.min.z, self.max.z, self.min.y, self.max.y),
+            BlockFace::Top | BlockFace::Bottom => (self.min.x, self.max.x, self.min.z, self.max.z),
+        };
+
+        self.touches(face) && u >= u_min && u <= u_max && v >= v_min && v <= v_max
+    }
+
+    fn flipped(&self) -> Self {
+        Self {
+            min: Point3::new(self.min.x, 1.0 - self.max.y, self.min.z),
+            max: Point3::new(self.max.x, 1.0 - self.min.y, self.max.z),
+        }
+    }
+
+    fn rotated(&self, rotation: &Rotation) -> Self {
+        let a = rotation.world_point(self.min);
+        let b = rotation.world_point(self.max);
+        Self {
+            min: Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+}
+
+/// The boxes a block state is built from, already rotated into world orientation.
+#[derive(Debug, Clone)]
+pub struct ShapeModel {
+    pub shape: Shape,
+    pub boxes: Vec<Aabb>,
+    full_faces: u8,
+}
+
+impl ShapeModel {
+    pub fn new<'a>(
+        shape: Shape,
+        rotation: &Rotation,
+        property: impl Fn(&str) -> Option<&'a str>,
+    ) -> Self {
+        let boxes = match shape {
+            Shape::Cube => vec![Aabb::FULL],
+            Shape::Slab => match property("type") {
+                Some("top") => vec![Aabb::pixels([0, 8, 0], [16, 16, 16])],
+                Some("double") => vec![Aabb::FULL],
+                _ => vec![Aabb::pixels([0, 0, 0], [16, 8, 16])],
+            },
+            Shape::Stairs => {
+                let boxes = [
+                    Aabb::pixels([0, 0, 0], [16, 8, 16]),
+                    Aabb::pixels([0, 8, 8], [16, 16, 16]),
+                ];
+                let upside_down = property("half") == Some("top");
+
+                boxes
+                    .iter()
+                    .map(|aabb| if upside_down { aabb.flipped() } else { *aabb })
+                    .map(|aabb| aabb.rotated(rotation))
+                    .collect()
+            }
+            Shape::Fence => vec![Aabb::pixels([6, 0, 6], [10, 16, 10])],
+            Shape::Pane => vec![Aabb::pixels([7, 0, 7], [9, 16, 9])],
+            Shape::Cross => Vec::new(),
+        };
+
+        let mut model = Self {
+            shape,
+            boxes,
+            full_faces: 0,
+        };
+        for face in BlockFace::ALL {
+            if model.covers_on_boundary(face) {
+                model.full_faces |= 1 << face as u8;
+            }
+        }
+
+        model
+    }
+
+    fn covers_on_boundary(&self, face: BlockFace) -> bool {
+        (0..16).all(|u| {
+            (0..16).all(|v| {
+                let (u, v) = ((u as f32 + 0.5) / 16.0, (v as f32 + 0.5) / 16.0);
+                self.boxes
+                    .iter()
+                    .any(|aabb| aabb.contains_on_face(face, u, v))
+            })
+        })
+    }
+
+    /// Whether this shape fills the whole side of the block facing `face`.
+    pub fn covers(&self, face: BlockFace) -> bool {
+        self.full_faces & (1 << face as u8) != 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.full_faces == 0b111111
+    }
+
+    /// Whether the shape grows extra boxes towards connecting neighbours.
+    pub fn connects(&self) -> bool {
+        matches!(self.shape, Shape::Fence | Shape::Pane)
+    }
+
+    /// The boxes of a connecting shape, given the horizontal faces it connects through.
+    pub fn connected_boxes(&self, connections: &[BlockFace]) -> Vec<Aabb> {
+        let mut boxes = self.boxes.clone();
+
+        for face in connections {
+            match self.shape {
+                Shape::Fence => {
+                    let ((x0, z0), (x1, z1)) = match face {
+                        BlockFace::Front => ((7, 10), (9, 16)),
+                        BlockFace::Back => ((7, 0), (9, 6)),
+                        BlockFace::Left => ((0, 7), (6, 9)),
+                        BlockFace::Right => ((10, 7), (16, 9)),
+                        _ => continue,
+                    };
+                    for (y0, y1) in [(6, 9), (12, 15)] {
+                        boxes.push(Aabb::pixels([x0, y0, z0], [x1, y1, z1]));
+                    }
+                }
+                Shape::Pane => {
+                    let (min, max) = match face {
+                        BlockFace::Front => ([7, 0, 9], [9, 16, 16]),
+                        BlockFace::Back => ([7, 0, 0], [9, 16, 7]),
+                        BlockFace::Left => ([0, 0, 7], [7, 16, 9]),
+                        BlockFace::Right => ([9, 0, 7], [16, 16, 9]),
+                        _ => continue,
+                    };
+                    boxes.push(Aabb::pixels(min, max));
+                }
+                _ => {}
+            }
+        }
+
+        boxes
+    }
+}
+
+/// The two diagonal quads of a [`Shape::Cross`], in block-local space.
+pub fn cross_quads() -> [[Point3<f32>; 4]; 2] {
+    [
+        [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ],
+        [
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 1.0),
+        ],
+    ]
+}
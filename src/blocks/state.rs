@@ -0,0 +1,111 @@
+use cgmath::{Deg, EuclideanSpace, InnerSpace, Matrix, Matrix3, Point2, Point3, Vector3};
+use serde::Deserialize;
+
+use super::{BlockFace, BlockId, ShapeModel};
+
+/// Compact id of one block together with one value for each of its properties.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockStateId(pub u16);
+
+impl BlockStateId {
+    pub const AIR: BlockStateId = BlockStateId(0);
+
+    pub fn is_air(&self) -> bool {
+        *self == Self::AIR
+    }
+}
+
+/// A named block property and every value it can take. The first value is the default.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Property {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockState {
+    pub block: BlockId,
+    pub(super) values: Vec<u8>,
+    pub(super) rotation: Rotation,
+    pub(super) model: ShapeModel,
+    pub(super) texture_indices: [u32; 6],
+}
+
+impl BlockState {
+    pub fn rotation(&self) -> &Rotation {
+        &self.rotation
+    }
+
+    pub fn model(&self) -> &ShapeModel {
+        &self.model
+    }
+}
+
+/// Orientation of a block model, derived from its `axis` or `facing` property.
+#[derive(Debug, Clone, Copy)]
+pub struct Rotation {
+    matrix: Matrix3<f32>,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self {
+            matrix: Matrix3::from_scale(1.0),
+        }
+    }
+}
+
+impl Rotation {
+    pub fn from_property(name: &str, value: &str) -> Option<Self> {
+        let matrix = match (name, value) {
+            ("axis", "y") => Matrix3::from_scale(1.0),
+            ("axis", "x") => Matrix3::from_angle_z(Deg(-90.0)),
+            ("axis", "z") => Matrix3::from_angle_x(Deg(90.0)),
+            ("facing", "south") => Matrix3::from_scale(1.0),
+            ("facing", "east") => Matrix3::from_angle_y(Deg(90.0)),
+            ("facing", "west") => Matrix3::from_angle_y(Deg(-90.0)),
+            ("facing", "north") => Matrix3::from_angle_y(Deg(180.0)),
+            ("facing", "up") => Matrix3::from_angle_x(Deg(-90.0)),
+            ("facing", "down") => Matrix3::from_angle_x(Deg(90.0)),
+            _ => return None,
+        };
+
+        let round = |axis: Vector3<f32>| axis.map(f32::round);
+        Some(Self {
+            matrix: Matrix3::from_cols(round(matrix.x), round(matrix.y), round(matrix.z)),
+        })
+    }
+
+    /// The face of the unrotated model that ends up facing `face` in the world.
+    pub fn model_face(&self, face: BlockFace) -> BlockFace {
+        let normal = self.matrix.transpose() * face.get_normal().to_vec();
+        BlockFace::from_normal(normal).unwrap_or(face)
+    }
+
+    /// Maps a point of the unit cube in world space back into model space.
+    pub fn model_point(&self, point: Point3<f32>) -> Point3<f32> {
+        let center = Vector3::new(0.5, 0.5, 0.5);
+        Point3::from_vec(self.matrix.transpose() * (point.to_vec() - center) + center)
+    }
+
+    /// Maps a point of the unit cube in model space into world space.
+    pub fn world_point(&self, point: Point3<f32>) -> Point3<f32> {
+        let center = Vector3::new(0.5, 0.5, 0.5);
+        Point3::from_vec(self.matrix * (point.to_vec() - center) + center)
+    }
+
+    /// Texture coordinates for the block-local `vertices` of a quad facing `face`,
+    /// so the model's texture follows the rotation.
+    pub fn tex_coords(&self, face: BlockFace, vertices: [Point3<f32>; 4]) -> [Point2<f32>; 4] {
+        let model_face = self.model_face(face);
+        let model_vertices = model_face.get_vertices(Point3::new(0.0, 0.0, 0.0));
+        let origin = model_vertices[0];
+        let u_dir = model_vertices[1] - origin;
+        let up_dir = model_vertices[3] - origin;
+
+        vertices.map(|vertex| {
+            let offset = self.model_point(vertex) - origin;
+            Point2::new(offset.dot(u_dir), 1.0 - offset.dot(up_dir))
+        })
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+
+use super::BlockRegistry;
+use crate::engine::{model::Material, object::Context};
+
+/// The texture array holding every block texture, shared by all chunk meshes.
+pub struct BlockTextures {
+    material: Material,
+    indices: TextureIndices,
+}
+
+/// Where each block texture sits in the texture array. Cheap to clone, so
+/// meshing jobs can carry it off the main thread.
+#[derive(Clone)]
+pub struct TextureIndices(Arc<[u32]>);
+
+impl TextureIndices {
+    /// Index into the texture array of a texture index returned by
+    /// [`super::Block::get_texture_index`].
+    pub fn array_index(&self, texture_index: u32) -> u32 {
+        self.0[texture_index as usize]
+    }
+}
+
+impl BlockTextures {
+    pub fn load(registry: &BlockRegistry, ctx: &Context) -> anyhow::Result<Self> {
+        let names: Vec<&str> = registry
+            .texture_names()
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let array = ctx.load_texture_array(&names)?;
+
+        let indices: Vec<u32> = names
+            .iter()
+            .map(|name| {
+                array
+                    .index(name)
+                    .ok_or_else(|| anyhow!("Texture {} is missing from the block array", name))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            material: ctx.create_material(&array, "Block Textures"),
+            indices: TextureIndices(indices.into()),
+        })
+    }
+
+    pub fn indices(&self) -> &TextureIndices {
+        &self.indices
+    }
+
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+}
@@ -0,0 +1,35 @@
+use super::ChunkSnapshot;
+use crate::{blocks::BlockFace, coords::LocalPos};
+
+impl ChunkSnapshot {
+    /// Ambient occlusion levels of the corners of a block face, in the vertex order
+    /// of [`BlockFace::get_box_vertices`]: 3 for open corners down to 0 for
+    /// corners enclosed on both sides. Each corner looks at the two blocks beside
+    /// it and the one diagonal to it, in the layer the face looks into.
+    pub(super) fn face_occlusion(&self, pos: LocalPos, face: BlockFace) -> [u32; 4] {
+        let [nx, ny, nz] = face.offset();
+        let occludes = |dx: i32, dy: i32, dz: i32| {
+            let state = self.block_at(pos, [nx + dx, ny + dy, nz + dz]);
+            self.registry.is_opaque_cube(state)
+        };
+
+        let unit = face.get_box_vertices([0.0; 3].into(), [1.0; 3].into());
+        unit.map(|corner| {
+            // Steps from the block's centre towards the corner, zero along the normal.
+            let step = |v: f32, normal: i32| if normal != 0 { 0 } else { v as i32 * 2 - 1 };
+            let (sx, sy, sz) = (step(corner.x, nx), step(corner.y, ny), step(corner.z, nz));
+            // Split the step into its two in-plane components, one per side block.
+            let (side_1, side_2) = match face {
+                BlockFace::Left | BlockFace::Right => (occludes(0, sy, 0), occludes(0, 0, sz)),
+                BlockFace::Top | BlockFace::Bottom => (occludes(sx, 0, 0), occludes(0, 0, sz)),
+                BlockFace::Front | BlockFace::Back => (occludes(sx, 0, 0), occludes(0, sy, 0)),
+            };
+
+            if side_1 && side_2 {
+                0
+            } else {
+                3 - side_1 as u32 - side_2 as u32 - occludes(sx, sy, sz) as u32
+            }
+        })
+    }
+}
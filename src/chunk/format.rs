@@ -0,0 +1,435 @@
+use std::{fmt, str, sync::Arc};
+
+use super::{CHUNK_VOLUME, Chunk};
+use crate::{
+    blocks::{BlockRegistry, BlockStateId},
+    coords::{ChunkPosition, LocalPos},
+    heightmap::{Heightmap, HeightmapKind},
+    palette::PalettedContainer,
+};
+
+const MAGIC: [u8; 4] = *b"MCCK";
+
+/// Version written by [`Chunk::encode`]. Bump it whenever the layout changes.
+pub const CHUNK_FORMAT_VERSION: u16 = 2;
+
+/// Why bytes could not be decoded into a chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkDecodeError {
+    /// The input ended in the middle of a field.
+    UnexpectedEnd,
+    /// The input does not start with the chunk magic bytes.
+    BadMagic,
+    UnsupportedVersion(u16),
+    /// A palette entry names a block the registry does not have.
+    UnknownBlock(String),
+    /// A palette entry sets a property its block does not have, or to a value
+    /// it cannot take.
+    UnknownProperty {
+        block: String,
+        property: String,
+        value: String,
+    },
+    /// The palette, index width and packed data do not describe a full chunk.
+    InvalidBlockData,
+    /// Bytes were left over after the last section.
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for ChunkDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "Chunk data ended unexpectedly"),
+            Self::BadMagic => write!(f, "Not chunk data"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "Unsupported chunk format version {}", version)
+            }
+            Self::UnknownBlock(name) => write!(f, "Unknown block {}", name),
+            Self::UnknownProperty {
+                block,
+                property,
+                value,
+            } => write!(f, "Block {} has no {}={}", block, property, value),
+            Self::InvalidBlockData => write!(f, "Invalid block data"),
+            Self::TrailingBytes(count) => {
+                write!(f, "{} unexpected bytes after the chunk data", count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChunkDecodeError {}
+
+impl Chunk {
+    /// Encodes the chunk's blocks. Every number is little endian, and every string
+    /// a `u16` byte count followed by UTF-8:
+    ///
+    /// | Field | Type |
+    /// |---|---|
+    /// | magic `MCCK` | 4 bytes |
+    /// | format version | `u16` |
+    /// | position x, y, z | 3 × `i32`, in chunks |
+    /// | palette length | `u16` |
+    /// | palette | per entry: block name, `u8` property count, then each property's name and value |
+    /// | bits per index | `u8`; 0 when the palette has one entry |
+    /// | packed index count | `u32` |
+    /// | packed indices | `u64` words, each holding `64 / bits` whole indices, lowest bits first, in y, z, x order |
+    /// | light length, light | `u32` byte count, then bytes; reserved, empty |
+    /// | block entities length, block entities | `u32` byte count, then bytes; reserved, empty |
+    ///
+    /// Palette entries name their block and property values rather than storing
+    /// state ids, so saved chunks still decode after blocks or property values are
+    /// added to the definitions. Heightmaps are rebuilt from the blocks on decode.
+    pub fn encode(&self) -> Vec<u8> {
+        let palette = self.blocks.palette();
+        let data = self.blocks.data();
+        let mut bytes = Vec::with_capacity(32 + palette.len() * 16 + data.len() * 8);
+
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&CHUNK_FORMAT_VERSION.to_le_bytes());
+        for coordinate in [self.position.x, self.position.y, self.position.z] {
+            bytes.extend_from_slice(&coordinate.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&(palette.len() as u16).to_le_bytes());
+        for &state in palette {
+            let block = self.registry.block_of(state);
+            push_str(&mut bytes, &block.name);
+            bytes.push(block.properties.len() as u8);
+            for property in &block.properties {
+                push_str(&mut bytes, &property.name);
+                let value = self.registry.property(state, &property.name);
+                push_str(&mut bytes, value.unwrap_or_default());
+            }
+        }
+        bytes.push(self.blocks.bits() as u8);
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        for word in data {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+
+        // Light and block entities are not stored yet.
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+
+        bytes
+    }
+
+    /// Decodes a chunk written by [`Chunk::encode`], checking every field against
+    /// the format and `registry`. Properties missing from a palette entry keep
+    /// their default value.
+    pub fn decode(bytes: &[u8], registry: Arc<BlockRegistry>) -> Result<Self, ChunkDecodeError> {
+        let mut reader = Reader { bytes };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(ChunkDecodeError::BadMagic);
+        }
+        let version = reader.u16()?;
+        if version != CHUNK_FORMAT_VERSION {
+            return Err(ChunkDecodeError::UnsupportedVersion(version));
+        }
+        let position = ChunkPosition::new(reader.i32()?, reader.i32()?, reader.i32()?);
+
+        let palette_len = reader.u16()? as usize;
+        let palette = (0..palette_len)
+            .map(|_| reader.block_state(&registry))
+            .collect::<Result<Vec<_>, _>>()?;
+        let bits = reader.u8()? as u32;
+        let word_count = reader.u32()? as usize;
+        // Checked up front so a corrupt count cannot trigger a huge allocation.
+        if word_count > CHUNK_VOLUME {
+            return Err(ChunkDecodeError::InvalidBlockData);
+        }
+        let data = (0..word_count)
+            .map(|_| reader.u64())
+            .collect::<Result<Vec<_>, _>>()?;
+        let blocks = PalettedContainer::from_parts(CHUNK_VOLUME, palette, bits, data)
+            .ok_or(ChunkDecodeError::InvalidBlockData)?;
+
+        // Reserved sections; nothing reads them yet.
+        for _ in 0..2 {
+            let len = reader.u32()? as usize;
+            reader.take(len)?;
+        }
+
+        if !reader.bytes.is_empty() {
+            return Err(ChunkDecodeError::TrailingBytes(reader.bytes.len()));
+        }
+
+        let mut chunk = Chunk::new(position, registry);
+        for pos in LocalPos::all() {
+            let state = blocks.get(pos.index());
+            if !state.is_air() {
+                chunk.non_air_count += 1;
+            }
+            if chunk.registry.is_opaque_cube(state) {
+                chunk.opaque_count += 1;
+            }
+        }
+        for (kind, heightmap) in HeightmapKind::ALL.iter().zip(&mut chunk.heightmaps) {
+            *heightmap =
+                Heightmap::build(|pos| kind.matches(&chunk.registry, blocks.get(pos.index())));
+        }
+        if chunk.non_air_count > 0 {
+            chunk.blocks = blocks;
+        }
+
+        Ok(chunk)
+    }
+}
+
+fn push_str(bytes: &mut Vec<u8>, string: &str) {
+    bytes.extend_from_slice(&(string.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+/// Reads little endian fields off the front of a byte slice.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ChunkDecodeError> {
+        if self.bytes.len() < len {
+            return Err(ChunkDecodeError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ChunkDecodeError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, ChunkDecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ChunkDecodeError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, ChunkDecodeError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, ChunkDecodeError> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, ChunkDecodeError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn str(&mut self) -> Result<&'a str, ChunkDecodeError> {
+        let len = self.u16()? as usize;
+        str::from_utf8(self.take(len)?).map_err(|_| ChunkDecodeError::InvalidBlockData)
+    }
+
+    /// A palette entry, resolved against `registry`.
+    fn block_state(&mut self, registry: &BlockRegistry) -> Result<BlockStateId, ChunkDecodeError> {
+        let name = self.str()?;
+        let mut state = registry
+            .get_by_name(name)
+            .map_err(|_| ChunkDecodeError::UnknownBlock(name.to_owned()))?
+            .default_state();
+
+        for _ in 0..self.u8()? {
+            let (property, value) = (self.str()?, self.str()?);
+            state = registry
+                .with_property(state, property, value)
+                .ok_or_else(|| ChunkDecodeError::UnknownProperty {
+                    block: name.to_owned(),
+                    property: property.to_owned(),
+                    value: value.to_owned(),
+                })?;
+        }
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::CHUNK_SIZE;
+
+    fn registry() -> Arc<BlockRegistry> {
+        Arc::new(BlockRegistry::load("blocks.ron").expect("Block definitions load"))
+    }
+
+    fn state(registry: &BlockRegistry, name: &str) -> BlockStateId {
+        registry.get_by_name(name).unwrap().default_state()
+    }
+
+    fn assert_same(a: &Chunk, b: &Chunk) {
+        assert_eq!(a.position, b.position);
+        assert_eq!(a.non_air_count, b.non_air_count);
+        assert_eq!(a.opaque_count, b.opaque_count);
+        for pos in LocalPos::all() {
+            assert_eq!(a.get_block(pos), b.get_block(pos), "block at {:?}", pos);
+        }
+        for kind in HeightmapKind::ALL {
+            for (x, z) in (0..CHUNK_SIZE).flat_map(|x| (0..CHUNK_SIZE).map(move |z| (x, z))) {
+                assert_eq!(a.height(kind, x, z), b.height(kind, x, z));
+            }
+        }
+    }
+
+    #[test]
+    fn empty_chunk_round_trips() {
+        let registry = registry();
+        let chunk = Chunk::new(ChunkPosition::new(-3, 7, 12), Arc::clone(&registry));
+
+        let decoded = Chunk::decode(&chunk.encode(), registry).unwrap();
+
+        assert_same(&chunk, &decoded);
+        assert!(decoded.is_empty());
+    }
+
+    #[test]
+    fn filled_chunk_round_trips() {
+        let registry = registry();
+        let mut chunk = Chunk::new(ChunkPosition::new(5, -2, -9), Arc::clone(&registry));
+        let stone = state(&registry, "stone");
+        let dirt = state(&registry, "dirt");
+        let glass = state(&registry, "glass");
+
+        for pos in LocalPos::all() {
+            let block = match (pos.x + pos.y * 3 + pos.z * 7) % 5 {
+                0 => stone,
+                1 => dirt,
+                2 => glass,
+                3 => BlockStateId::AIR,
+                _ => BlockStateId(pos.index() as u16 % registry.state_count() as u16),
+            };
+            chunk.set_block(pos, block);
+        }
+
+        let decoded = Chunk::decode(&chunk.encode(), registry).unwrap();
+
+        assert_same(&chunk, &decoded);
+    }
+
+    #[test]
+    fn opaque_chunk_round_trips() {
+        let registry = registry();
+        let mut chunk = Chunk::new(ChunkPosition::new(0, 0, 0), Arc::clone(&registry));
+        let stone = state(&registry, "stone");
+        for pos in LocalPos::all() {
+            chunk.set_block(pos, stone);
+        }
+
+        let decoded = Chunk::decode(&chunk.encode(), registry).unwrap();
+
+        assert_same(&chunk, &decoded);
+        assert!(decoded.is_opaque());
+    }
+
+    #[test]
+    fn chunks_decode_after_blocks_are_added() {
+        let registry = registry();
+        let mut chunk = Chunk::new(ChunkPosition::new(0, 0, 0), Arc::clone(&registry));
+        let stone = state(&registry, "stone");
+        let log = registry
+            .with_property(state(&registry, "oak_log"), "axis", "x")
+            .unwrap();
+        chunk.set_block(LocalPos::new(0, 0, 0), stone);
+        chunk.set_block(LocalPos::new(1, 0, 0), log);
+
+        // Insert a block right after air, shifting the state ids of every other one.
+        let source = crate::engine::resources::load_string("blocks.ron").unwrap();
+        let mut definitions: Vec<crate::blocks::Block> = ron::from_str(&source).unwrap();
+        for block in &mut definitions[1..] {
+            block.id.0 += 1;
+        }
+        let mut added = definitions[1].clone();
+        added.name = "added".to_owned();
+        added.id.0 = 1;
+        definitions.push(added);
+        let shifted = Arc::new(BlockRegistry::from_definitions(definitions).unwrap());
+
+        let decoded = Chunk::decode(&chunk.encode(), Arc::clone(&shifted)).unwrap();
+
+        assert_ne!(state(&shifted, "stone"), stone);
+        assert_eq!(
+            decoded.get_block(LocalPos::new(0, 0, 0)),
+            state(&shifted, "stone")
+        );
+        assert_eq!(
+            shifted.property(decoded.get_block(LocalPos::new(1, 0, 0)), "axis"),
+            Some("x")
+        );
+    }
+
+    #[test]
+    fn truncated_input_is_rejected() {
+        let registry = registry();
+        let mut chunk = Chunk::new(ChunkPosition::new(1, 2, 3), Arc::clone(&registry));
+        chunk.set_block(LocalPos::new(4, 5, 6), state(&registry, "stone"));
+        let bytes = chunk.encode();
+
+        for len in 0..bytes.len() {
+            assert_eq!(
+                Chunk::decode(&bytes[..len], Arc::clone(&registry)).err(),
+                Some(ChunkDecodeError::UnexpectedEnd),
+                "length {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_input_is_rejected() {
+        let registry = registry();
+        let mut chunk = Chunk::new(ChunkPosition::new(1, 2, 3), Arc::clone(&registry));
+        chunk.set_block(LocalPos::new(0, 0, 0), state(&registry, "stone"));
+        chunk.set_block(LocalPos::new(1, 0, 0), state(&registry, "dirt"));
+        let bytes = chunk.encode();
+        let decode = |bytes: &[u8]| Chunk::decode(bytes, Arc::clone(&registry)).err();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(decode(&bad_magic), Some(ChunkDecodeError::BadMagic));
+
+        let mut bad_version = bytes.clone();
+        bad_version[4..6].copy_from_slice(&99u16.to_le_bytes());
+        assert_eq!(
+            decode(&bad_version),
+            Some(ChunkDecodeError::UnsupportedVersion(99))
+        );
+
+        // The palette starts after magic, version, position and palette length,
+        // with the name of air.
+        let mut unknown_block = bytes.clone();
+        unknown_block[22..25].copy_from_slice(b"zzz");
+        assert_eq!(
+            decode(&unknown_block),
+            Some(ChunkDecodeError::UnknownBlock("zzz".to_owned()))
+        );
+
+        let mut invalid_name = bytes.clone();
+        invalid_name[22] = 0xFF;
+        assert_eq!(
+            decode(&invalid_name),
+            Some(ChunkDecodeError::InvalidBlockData)
+        );
+
+        // Three palette entries: air, stone, dirt. Then bits per index.
+        let mut bad_bits = bytes.clone();
+        bad_bits[41] = 1;
+        assert_eq!(decode(&bad_bits), Some(ChunkDecodeError::InvalidBlockData));
+
+        // Index 3 does not exist in the three entry palette.
+        let mut bad_index = bytes.clone();
+        bad_index[46] = 0b11;
+        assert_eq!(decode(&bad_index), Some(ChunkDecodeError::InvalidBlockData));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(decode(&trailing), Some(ChunkDecodeError::TrailingBytes(1)));
+    }
+}
@@ -0,0 +1,150 @@
+use cgmath::{Point3, Vector3};
+
+use super::{
+    CHUNK_SIZE, ChunkSnapshot, MeshData,
+    mesh::{push_quad, tex_transform},
+};
+use crate::{
+    blocks::{BlockFace, Shape, ShapeModel},
+    coords::LocalPos,
+};
+
+/// What a visible block face looks like. Adjacent faces that look the same are
+/// merged into one quad.
+#[derive(Clone, Copy, PartialEq)]
+pub(super) struct FaceKey {
+    pub render_class: usize,
+    pub tex_index: u32,
+    pub tex_transform: u32,
+    pub ao: [u32; 4],
+}
+
+impl FaceKey {
+    /// Faces shaded differently at their corners are kept apart, as stretching
+    /// the shading over a merged quad would change how it looks.
+    fn can_merge(&self) -> bool {
+        self.ao.iter().all(|&ao| ao == self.ao[0])
+    }
+}
+
+/// Whether blocks of this model are meshed by [`ChunkSnapshot::greedy_slice`]. Full
+/// models look like a unit cube from outside, whatever boxes they are built of.
+pub(super) fn is_greedy(model: &ShapeModel) -> bool {
+    model.is_full() && !model.connects() && model.shape != Shape::Cross
+}
+
+/// Axis along the normal of `face`, then the two axes spanning it.
+pub(super) fn face_axes(face: BlockFace) -> (usize, usize, usize) {
+    match face {
+        BlockFace::Left | BlockFace::Right => (0, 1, 2),
+        BlockFace::Top | BlockFace::Bottom => (1, 0, 2),
+        BlockFace::Front | BlockFace::Back => (2, 0, 1),
+    }
+}
+
+impl ChunkSnapshot {
+    /// Meshes the `face` sides of the full blocks in the layer `slice` blocks along
+    /// the face's axis, merging equal adjacent faces into rectangles.
+    pub(super) fn greedy_slice(&self, face: BlockFace, slice: usize, meshes: &mut [MeshData; 3]) {
+        let (registry, textures) = (&self.registry, &self.textures);
+        let (normal_axis, a_axis, b_axis) = face_axes(face);
+        let cell = |a: usize, b: usize| {
+            let mut coords = [0; 3];
+            coords[normal_axis] = slice;
+            coords[a_axis] = a;
+            coords[b_axis] = b;
+            LocalPos::new(coords[0], coords[1], coords[2])
+        };
+
+        let mut mask = [[None; CHUNK_SIZE]; CHUNK_SIZE];
+        for (a, row) in mask.iter_mut().enumerate() {
+            for (b, key) in row.iter_mut().enumerate() {
+                let pos = cell(a, b);
+                let id = self.get_block(pos);
+                if id.is_air() {
+                    continue;
+                }
+                let state = registry.state(id);
+                let block = registry.get(state.block);
+                if !is_greedy(state.model()) || self.should_hide_face(pos, face, block) {
+                    continue;
+                }
+
+                *key = Some(FaceKey {
+                    render_class: block.render_class as usize,
+                    tex_index: textures.array_index(block.get_texture_index(state, face)),
+                    tex_transform: tex_transform(state.rotation(), face),
+                    ao: self.face_occlusion(pos, face),
+                });
+            }
+        }
+
+        let corner = |a: usize, b: usize| {
+            let pos = cell(a, b);
+            Point3::new(pos.x as f32, pos.y as f32, pos.z as f32)
+        };
+        merge_faces(&mut mask, CHUNK_SIZE, |(a, b), (last_a, last_b), key| {
+            push_box_face(
+                face,
+                corner(a, b),
+                corner(last_a, last_b) + Vector3::new(1.0, 1.0, 1.0),
+                key,
+                &mut meshes[key.render_class],
+            );
+        });
+    }
+}
+
+/// Splits the faces in the first `size` rows and columns of `mask` into
+/// rectangles of equal faces, handing each to `emit` with its first and last cell.
+pub(super) fn merge_faces(
+    mask: &mut [[Option<FaceKey>; CHUNK_SIZE]; CHUNK_SIZE],
+    size: usize,
+    mut emit: impl FnMut((usize, usize), (usize, usize), FaceKey),
+) {
+    for a in 0..size {
+        for b in 0..size {
+            let Some(key) = mask[a][b] else {
+                continue;
+            };
+
+            let mut width = 1;
+            while key.can_merge() && a + width < size && mask[a + width][b] == Some(key) {
+                width += 1;
+            }
+            let mut height = 1;
+            while key.can_merge()
+                && b + height < size
+                && (a..a + width).all(|row| mask[row][b + height] == Some(key))
+            {
+                height += 1;
+            }
+            for row in &mut mask[a..a + width] {
+                row[b..b + height].fill(None);
+            }
+
+            emit((a, b), (a + width - 1, b + height - 1), key);
+        }
+    }
+}
+
+/// Adds the `face` side of the box spanning `min` to `max`, relative to the chunk origin.
+pub(super) fn push_box_face(
+    face: BlockFace,
+    min: Point3<f32>,
+    max: Point3<f32>,
+    key: FaceKey,
+    mesh: &mut MeshData,
+) {
+    // Texture coordinates follow the position, so the texture repeats once per
+    // block over a merged quad.
+    push_quad(
+        mesh,
+        face.get_box_vertices(min, max),
+        face as u32,
+        key.tex_index,
+        key.tex_transform,
+        key.ao,
+        false,
+    );
+}
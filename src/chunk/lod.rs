@@ -0,0 +1,182 @@
+use cgmath::Point3;
+
+use super::{
+    CHUNK_SIZE, Chunk, ChunkSnapshot, MeshData,
+    greedy::{self, FaceKey},
+    mesh::{NO_OCCLUSION, tex_transform},
+};
+use crate::{
+    blocks::{BlockFace, BlockRegistry, BlockStateId},
+    coords::LocalPos,
+    engine::model::RenderClass,
+};
+
+/// Coarsest level of detail. At level `n` a chunk is meshed in cells of `2^n`
+/// blocks on a side.
+pub const MAX_LOD: u8 = 3;
+
+impl ChunkSnapshot {
+    /// Builds the mesh of a chunk at a level of detail above 0. Each cell becomes
+    /// one cube of the block most common on its surface, or air if less than half
+    /// of it is filled with full blocks; other blocks, such as plants, are left out.
+    ///
+    /// Faces on the chunk border are culled against the border cells of
+    /// neighbours at the same level. Towards other neighbours they are kept as
+    /// skirts over the seams, whose surfaces do not line up with this one, except
+    /// for translucent faces, which would show through the surface as walls.
+    pub(super) fn build_lod_mesh(&self) -> [MeshData; 3] {
+        let scale = 1 << self.lod;
+        let size = CHUNK_SIZE / scale;
+        let grid: Vec<BlockStateId> = (0..size * size * size)
+            .map(|index| {
+                let (cell_y, cell_z, cell_x) =
+                    (index / (size * size), index / size % size, index % size);
+                downsample_cell(&self.registry, scale, [cell_x, cell_y, cell_z], |pos| {
+                    self.get_block(pos)
+                })
+            })
+            .collect();
+        let cell = |coords: [usize; 3]| grid[(coords[1] * size + coords[2]) * size + coords[0]];
+
+        let mut meshes: [MeshData; 3] = Default::default();
+        for face in BlockFace::ALL {
+            let (normal_axis, a_axis, b_axis) = greedy::face_axes(face);
+            let step = face.offset()[normal_axis];
+
+            for slice in 0..size {
+                let coords = |a: usize, b: usize| {
+                    let mut coords = [0; 3];
+                    coords[normal_axis] = slice;
+                    coords[a_axis] = a;
+                    coords[b_axis] = b;
+                    coords
+                };
+
+                let mut mask = [[None; CHUNK_SIZE]; CHUNK_SIZE];
+                for (a, row) in mask.iter_mut().enumerate().take(size) {
+                    for (b, key) in row.iter_mut().enumerate().take(size) {
+                        let id = cell(coords(a, b));
+                        if id.is_air() {
+                            continue;
+                        }
+                        let state = self.registry.state(id);
+                        let block = self.registry.get(state.block);
+
+                        let next = slice as i32 + step;
+                        let neighbor = if next >= 0 && next < size as i32 {
+                            let mut neighbor = coords(a, b);
+                            neighbor[normal_axis] = next as usize;
+                            Some(cell(neighbor))
+                        } else {
+                            self.lod_borders[face as usize]
+                                .as_ref()
+                                .map(|border| border[a * size + b])
+                        };
+                        match neighbor {
+                            Some(neighbor)
+                                if !neighbor.is_air()
+                                    && self.registry.block_of(neighbor).hides_face_of(block) =>
+                            {
+                                continue;
+                            }
+                            None if block.render_class == RenderClass::Translucent => continue,
+                            _ => {}
+                        }
+
+                        *key = Some(FaceKey {
+                            render_class: block.render_class as usize,
+                            tex_index: self
+                                .textures
+                                .array_index(block.get_texture_index(state, face)),
+                            tex_transform: tex_transform(state.rotation(), face),
+                            ao: NO_OCCLUSION,
+                        });
+                    }
+                }
+
+                // Corner of a cell in blocks, `end` cells further along each axis.
+                let corner = |a: usize, b: usize, end: usize| {
+                    let [x, y, z] = coords(a, b).map(|v| ((v + end) * scale) as f32);
+                    Point3::new(x, y, z)
+                };
+                greedy::merge_faces(&mut mask, size, |(a, b), (last_a, last_b), key| {
+                    greedy::push_box_face(
+                        face,
+                        corner(a, b, 0),
+                        corner(last_a, last_b, 1),
+                        key,
+                        &mut meshes[key.render_class],
+                    );
+                });
+            }
+        }
+
+        meshes
+    }
+}
+
+/// The cells of `chunk`'s layer that borders a chunk meshed at `lod` through
+/// that chunk's `face`, indexed by the two axes spanning the face as
+/// `a * size + b`, see [`greedy::face_axes`].
+pub(super) fn border_cells(chunk: &Chunk, face: BlockFace, lod: u8) -> Box<[BlockStateId]> {
+    let scale = 1 << lod;
+    let size = CHUNK_SIZE / scale;
+    let (normal_axis, a_axis, b_axis) = greedy::face_axes(face);
+    // The layer of `chunk` touching the meshed chunk is on the opposite side.
+    let layer = if face.offset()[normal_axis] > 0 {
+        0
+    } else {
+        size - 1
+    };
+
+    (0..size * size)
+        .map(|index| {
+            let mut coords = [0; 3];
+            coords[normal_axis] = layer;
+            coords[a_axis] = index / size;
+            coords[b_axis] = index % size;
+            downsample_cell(&chunk.registry, scale, coords, |pos| chunk.get_block(pos))
+        })
+        .collect()
+}
+
+/// The block standing for the cell of `scale`³ blocks at `cell` on the coarser
+/// grid: the full block most common on its surface, or air if less than half
+/// of it is filled with full blocks.
+fn downsample_cell(
+    registry: &BlockRegistry,
+    scale: usize,
+    [cell_x, cell_y, cell_z]: [usize; 3],
+    get_block: impl Fn(LocalPos) -> BlockStateId,
+) -> BlockStateId {
+    let mut filled = 0;
+    // Topmost full block of every column, with how often it appears.
+    let mut surface: Vec<(BlockStateId, usize)> = Vec::new();
+    for x in cell_x * scale..(cell_x + 1) * scale {
+        for z in cell_z * scale..(cell_z + 1) * scale {
+            let mut top = None;
+            for y in cell_y * scale..(cell_y + 1) * scale {
+                let id = get_block(LocalPos::new(x, y, z));
+                if !id.is_air() && greedy::is_greedy(registry.state(id).model()) {
+                    filled += 1;
+                    top = Some(id);
+                }
+            }
+
+            if let Some(top) = top {
+                match surface.iter_mut().find(|(id, _)| *id == top) {
+                    Some((_, count)) => *count += 1,
+                    None => surface.push((top, 1)),
+                }
+            }
+        }
+    }
+
+    if filled * 2 >= scale * scale * scale
+        && let Some(&(id, _)) = surface.iter().max_by_key(|(_, count)| *count)
+    {
+        id
+    } else {
+        BlockStateId::AIR
+    }
+}
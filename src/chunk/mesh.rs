@@ -0,0 +1,304 @@
+use std::{borrow::Cow, sync::Arc};
+
+use cgmath::{EuclideanSpace, Point3, Vector3};
+
+use super::{CHUNK_SIZE, Chunk, greedy, lod};
+use crate::{
+    blocks::{
+        Block, BlockFace, BlockRegistry, BlockStateId, Rotation, Shape, ShapeModel, TextureIndices,
+        cross_quads,
+    },
+    coords::{ChunkPosition, LocalPos},
+    engine::model::ChunkVertex,
+    world::World,
+};
+
+/// Edge length of a snapshot: the chunk plus one block of its neighbours on every side.
+const PADDED_SIZE: usize = CHUNK_SIZE + 2;
+
+#[derive(Default)]
+pub struct MeshData {
+    pub vertices: Vec<ChunkVertex>,
+    pub indices: Vec<u32>,
+    /// Centre of every quad relative to the chunk origin, with where its indices
+    /// start, so translucent quads can be sorted.
+    pub quads: Vec<(Point3<f32>, usize)>,
+}
+
+impl MeshData {
+    fn append(&mut self, mut other: MeshData) {
+        let base_vertex = self.vertices.len() as u32;
+        let base_index = self.indices.len();
+        self.vertices.append(&mut other.vertices);
+        self.indices
+            .extend(other.indices.iter().map(|&i| i + base_vertex));
+        self.quads.extend(
+            other
+                .quads
+                .iter()
+                .map(|&(center, start)| (center, start + base_index)),
+        );
+    }
+}
+
+/// An immutable copy of a chunk's blocks and the layer of blocks around it,
+/// everything its mesh depends on, so it can be meshed away from the world.
+/// Blocks of neighbours that are not loaded, or meshed at another level of
+/// detail, read as air; the faces towards them are kept to cover the seams.
+pub struct ChunkSnapshot {
+    pub position: ChunkPosition,
+    /// Blocks from -1 to `CHUNK_SIZE` on every axis, in local coordinates.
+    blocks: Box<[BlockStateId]>,
+    pub(super) registry: Arc<BlockRegistry>,
+    pub(super) textures: TextureIndices,
+    /// See [`Chunk::lod`].
+    pub(super) lod: u8,
+    /// Above level 0, the border cells of the neighbour through each face, from
+    /// [`lod::border_cells`], if it is loaded at the same level.
+    pub(super) lod_borders: [Option<Box<[BlockStateId]>>; 6],
+}
+
+impl ChunkSnapshot {
+    pub fn capture(chunk: &Chunk, world: &World, textures: TextureIndices) -> Self {
+        let mut snapshot = Self {
+            position: chunk.position,
+            blocks: vec![BlockStateId::AIR; PADDED_SIZE.pow(3)].into_boxed_slice(),
+            registry: Arc::clone(&chunk.registry),
+            textures,
+            lod: chunk.lod,
+            lod_borders: Default::default(),
+        };
+
+        if chunk.lod > 0 {
+            for face in BlockFace::ALL {
+                snapshot.lod_borders[face as usize] = world
+                    .get_chunk(&chunk.position.neighbor(face))
+                    .filter(|neighbor| neighbor.lod == chunk.lod)
+                    .map(|neighbor| lod::border_cells(neighbor, face, chunk.lod));
+            }
+        }
+
+        let size = CHUNK_SIZE as i32;
+        let chunks = std::iter::once(chunk).chain(
+            chunk
+                .position
+                .surrounding()
+                .filter_map(|neighbor| world.get_chunk(&neighbor))
+                .filter(|neighbor| neighbor.lod == chunk.lod),
+        );
+        for source in chunks.filter(|source| !source.is_empty()) {
+            // The cells of `source` that fall inside the snapshot, per axis.
+            let range = |source: i32, own: i32| match source - own {
+                -1 => -1..=-1,
+                0 => 0..=size - 1,
+                _ => size..=size,
+            };
+            let (position, own) = (source.position, chunk.position);
+            for x in range(position.x, own.x) {
+                for y in range(position.y, own.y) {
+                    for z in range(position.z, own.z) {
+                        let local = LocalPos::new(
+                            x.rem_euclid(size) as usize,
+                            y.rem_euclid(size) as usize,
+                            z.rem_euclid(size) as usize,
+                        );
+                        snapshot.blocks[Self::index(x, y, z)] = source.get_block(local);
+                    }
+                }
+            }
+        }
+
+        snapshot
+    }
+
+    fn index(x: i32, y: i32, z: i32) -> usize {
+        let padded = |v: i32| (v + 1) as usize;
+        (padded(y) * PADDED_SIZE + padded(z)) * PADDED_SIZE + padded(x)
+    }
+
+    pub(super) fn get_block(&self, pos: LocalPos) -> BlockStateId {
+        self.blocks[Self::index(pos.x as i32, pos.y as i32, pos.z as i32)]
+    }
+
+    /// The block at an offset from `pos`, at most one block outside the chunk.
+    pub(super) fn block_at(&self, pos: LocalPos, [dx, dy, dz]: [i32; 3]) -> BlockStateId {
+        self.blocks[Self::index(pos.x as i32 + dx, pos.y as i32 + dy, pos.z as i32 + dz)]
+    }
+
+    /// Builds one mesh per [`RenderClass`](crate::engine::model::RenderClass),
+    /// in `RenderClass::ALL` order.
+    pub fn build_mesh(&self) -> [MeshData; 3] {
+        if self.lod > 0 {
+            return self.build_lod_mesh();
+        }
+
+        let mut meshes: [MeshData; 3] = Default::default();
+
+        for pos in LocalPos::all() {
+            self.mesh_block(pos, &mut meshes);
+        }
+        for face in BlockFace::ALL {
+            for slice in 0..CHUNK_SIZE {
+                let mut slice_meshes: [MeshData; 3] = Default::default();
+                self.greedy_slice(face, slice, &mut slice_meshes);
+                for (mesh, slice_mesh) in meshes.iter_mut().zip(slice_meshes) {
+                    mesh.append(slice_mesh);
+                }
+            }
+        }
+
+        meshes
+    }
+
+    /// Meshes a block that [`ChunkSnapshot::greedy_slice`] does not handle.
+    fn mesh_block(&self, pos: LocalPos, meshes: &mut [MeshData; 3]) {
+        let id = self.get_block(pos);
+        if id.is_air() {
+            return;
+        }
+        let state = self.registry.state(id);
+        let block = self.registry.get(state.block);
+        let model = state.model();
+        if greedy::is_greedy(model) {
+            return;
+        }
+        let mesh = &mut meshes[block.render_class as usize];
+
+        let offset = Vector3::new(pos.x as f32, pos.y as f32, pos.z as f32);
+
+        if model.shape == Shape::Cross {
+            let tex_index = self
+                .textures
+                .array_index(block.get_texture_index(state, BlockFace::Front));
+            for quad in cross_quads() {
+                push_quad(
+                    mesh,
+                    quad.map(|corner| corner + offset),
+                    ChunkVertex::DIAGONAL,
+                    tex_index,
+                    0,
+                    NO_OCCLUSION,
+                    true,
+                );
+            }
+            return;
+        }
+
+        let boxes = if model.connects() {
+            Cow::Owned(model.connected_boxes(&self.connections(pos, model)))
+        } else {
+            Cow::Borrowed(&model.boxes)
+        };
+
+        for aabb in boxes.iter() {
+            for face in BlockFace::ALL {
+                if aabb.touches(face) && self.should_hide_face(pos, face, block) {
+                    continue;
+                }
+
+                push_quad(
+                    mesh,
+                    face.get_box_vertices(aabb.min, aabb.max)
+                        .map(|corner| corner + offset),
+                    face as u32,
+                    self.textures
+                        .array_index(block.get_texture_index(state, face)),
+                    tex_transform(state.rotation(), face),
+                    NO_OCCLUSION,
+                    false,
+                );
+            }
+        }
+    }
+
+    pub(super) fn should_hide_face(&self, pos: LocalPos, face: BlockFace, block: &Block) -> bool {
+        let neighbor = self.block_at(pos, face.offset());
+        !neighbor.is_air()
+            && self.registry.block_of(neighbor).hides_face_of(block)
+            && self
+                .registry
+                .state(neighbor)
+                .model()
+                .covers(face.opposite())
+    }
+
+    /// Horizontal faces a fence or pane at the given position connects through.
+    fn connections(&self, pos: LocalPos, model: &ShapeModel) -> Vec<BlockFace> {
+        [
+            BlockFace::Front,
+            BlockFace::Back,
+            BlockFace::Left,
+            BlockFace::Right,
+        ]
+        .into_iter()
+        .filter(|face| {
+            let neighbor = self.block_at(pos, face.offset());
+            let neighbor_model = self.registry.state(neighbor).model();
+            !neighbor.is_air()
+                && (neighbor_model.shape == model.shape
+                    || (self.registry.block_of(neighbor).solid && neighbor_model.is_full()))
+        })
+        .collect()
+    }
+}
+
+/// Ambient occlusion of quads that are not shaded, such as plants and partial blocks.
+pub(super) const NO_OCCLUSION: [u32; 4] = [3; 4];
+
+/// The [`ChunkVertex`] texture transform that turns a face's texture the way
+/// `rotation` turns the block.
+pub(super) fn tex_transform(rotation: &Rotation, face: BlockFace) -> u32 {
+    let [uv0, uv1, _, uv3] = rotation.tex_coords(face, face.get_vertices(Point3::origin()));
+    // Unrotated, corner 1 is one step along u from corner 0, and corner 3 one
+    // step against v; these are where the rotation takes u and v.
+    let (u, v) = (uv1 - uv0, uv0 - uv3);
+    let swap = u.x.abs() < 0.5;
+    let (flip_u, flip_v) = if swap {
+        (v.x < 0.0, u.y < 0.0)
+    } else {
+        (u.x < 0.0, v.y < 0.0)
+    };
+    swap as u32 | (flip_u as u32) << 1 | (flip_v as u32) << 2
+}
+
+/// Adds a quad facing `face` with corners in counter-clockwise order, relative
+/// to the chunk origin. The quad is split along the diagonal with the lighter
+/// corners, so occlusion shades both triangles evenly instead of streaking
+/// along the split.
+pub(super) fn push_quad(
+    mesh: &mut MeshData,
+    positions: [Point3<f32>; 4],
+    face: u32,
+    tex_index: u32,
+    tex_transform: u32,
+    ao: [u32; 4],
+    double_sided: bool,
+) {
+    let base = mesh.vertices.len() as u32;
+    let center = Point3::centroid(&positions);
+    mesh.quads.push((center, mesh.indices.len()));
+    for (corner, (position, ao)) in positions.into_iter().zip(ao).enumerate() {
+        let position = position.map(|v| (v * 16.0).round() as u32);
+        mesh.vertices.push(ChunkVertex::new(
+            position.into(),
+            face,
+            corner as u32,
+            tex_index,
+            ao,
+            ChunkVertex::MAX_LIGHT,
+            tex_transform,
+        ));
+    }
+
+    if ao[0] + ao[2] >= ao[1] + ao[3] {
+        mesh.indices
+            .extend_from_slice(&[base, base + 1, base + 2, base + 2, base + 3, base]);
+    } else {
+        mesh.indices
+            .extend_from_slice(&[base + 1, base + 2, base + 3, base + 3, base, base + 1]);
+    }
+    if double_sided {
+        mesh.indices
+            .extend_from_slice(&[base, base + 2, base + 1, base + 2, base, base + 3]);
+    }
+}
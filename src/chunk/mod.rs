@@ -0,0 +1,210 @@
+mod ao;
+mod format;
+mod greedy;
+mod lod;
+mod mesh;
+mod translucent;
+
+use std::sync::Arc;
+
+use cgmath::Point3;
+
+pub use lod::MAX_LOD;
+pub use mesh::{ChunkSnapshot, MeshData};
+
+use crate::{
+    blocks::{BlockRegistry, BlockStateId, BlockTextures},
+    coords::{ChunkPosition, LocalPos},
+    engine::{
+        model::{Model, RenderClass},
+        object::Context,
+    },
+    heightmap::{Heightmap, HeightmapKind},
+    palette::PalettedContainer,
+    world::World,
+};
+
+/// Edge length of the cubic chunks the world is split into, on every axis.
+pub const CHUNK_SIZE: usize = 16;
+const CHUNK_VOLUME: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
+
+/// A 16×16×16 cube of blocks, stored palette compressed. All-air chunks store
+/// no block indices and are never meshed.
+pub struct Chunk {
+    pub position: ChunkPosition,
+    blocks: PalettedContainer,
+    non_air_count: u16,
+    opaque_count: u16,
+    /// One heightmap per [`HeightmapKind`], in [`HeightmapKind::ALL`] order.
+    heightmaps: [Heightmap; 3],
+    pub mesh: Option<Model>,
+    translucent: Option<translucent::TranslucentMesh>,
+    /// Level of detail the chunk is meshed at, from 0 for full resolution to
+    /// [`MAX_LOD`].
+    pub lod: u8,
+    pub need_rebuilt: bool,
+    /// Identifies the latest meshing job, so results of older jobs are dropped.
+    pub mesh_generation: u64,
+    /// Whether blocks changed since the chunk was generated or last saved.
+    pub modified: bool,
+    registry: Arc<BlockRegistry>,
+}
+
+impl Chunk {
+    pub fn new(position: ChunkPosition, registry: Arc<BlockRegistry>) -> Self {
+        Self {
+            position,
+            blocks: PalettedContainer::new(CHUNK_VOLUME, BlockStateId::AIR),
+            non_air_count: 0,
+            opaque_count: 0,
+            heightmaps: Default::default(),
+            mesh: None,
+            translucent: None,
+            lod: 0,
+            need_rebuilt: true,
+            mesh_generation: 0,
+            modified: false,
+            registry,
+        }
+    }
+
+    pub fn get_block(&self, pos: LocalPos) -> BlockStateId {
+        self.blocks.get(pos.index())
+    }
+
+    /// Writes a block. Returns whether anything changed.
+    pub fn set_block(&mut self, pos: LocalPos, block: BlockStateId) -> bool {
+        debug_assert!((block.0 as usize) < self.registry.state_count());
+        if self.is_empty() && block.is_air() {
+            return false;
+        }
+
+        let old = self.blocks.set(pos.index(), block);
+        if old == block {
+            return false;
+        }
+
+        match (old.is_air(), block.is_air()) {
+            (true, false) => self.non_air_count += 1,
+            (false, true) => self.non_air_count -= 1,
+            _ => {}
+        }
+        match (
+            self.registry.is_opaque_cube(old),
+            self.registry.is_opaque_cube(block),
+        ) {
+            (false, true) => self.opaque_count += 1,
+            (true, false) => self.opaque_count -= 1,
+            _ => {}
+        }
+
+        for (kind, heightmap) in HeightmapKind::ALL.iter().zip(&mut self.heightmaps) {
+            let blocks = &self.blocks;
+            let registry = &self.registry;
+            heightmap.update(pos, kind.matches(registry, block), |below| {
+                kind.matches(registry, blocks.get(LocalPos { y: below, ..pos }.index()))
+            });
+        }
+
+        // Drop the palette built up so far once the chunk is all air again.
+        if self.non_air_count == 0 {
+            self.blocks = PalettedContainer::new(CHUNK_VOLUME, BlockStateId::AIR);
+        }
+        self.need_rebuilt = true;
+        self.modified = true;
+        true
+    }
+
+    /// Local height of the highest block of the given kind in a column, if any.
+    pub fn height(&self, kind: HeightmapKind, x: usize, z: usize) -> Option<usize> {
+        self.heightmaps[kind as usize].get(x, z)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.non_air_count == 0
+    }
+
+    /// Whether every block is a full opaque cube, hiding whatever is behind it.
+    pub fn is_opaque(&self) -> bool {
+        self.opaque_count as usize == CHUNK_VOLUME
+    }
+
+    /// Whether the mesh would be empty: the chunk has no blocks, or it is opaque
+    /// and surrounded by opaque chunks.
+    pub fn is_hidden(&self, world: &World) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        self.is_opaque()
+            && self.position.neighbors().iter().all(|neighbor| {
+                world
+                    .get_chunk(neighbor)
+                    .is_some_and(|chunk| chunk.is_opaque())
+            })
+    }
+
+    /// Uploads a mesh built by [`ChunkSnapshot::build_mesh`]. Chunks without geometry get no model.
+    pub fn upload_mesh(
+        &mut self,
+        data: [MeshData; 3],
+        textures: &BlockTextures,
+        ctx: &mut Context,
+    ) {
+        self.translucent = None;
+        if data.iter().all(|mesh| mesh.indices.is_empty()) {
+            self.mesh = None;
+            return;
+        }
+
+        let label = format!(
+            "Chunk({}-{}-{})",
+            self.position.x, self.position.y, self.position.z
+        );
+        let camera = ctx.get_camera().position;
+        let mut meshes = Vec::new();
+        for (render_class, mesh) in RenderClass::ALL.into_iter().zip(data) {
+            if mesh.indices.is_empty() {
+                continue;
+            }
+
+            let label = format!("{} {:?}", label, render_class);
+            if render_class == RenderClass::Translucent {
+                let indices =
+                    translucent::back_to_front(&mesh.indices, &mesh.quads, self.local_eye(camera));
+                let gpu_mesh =
+                    ctx.create_chunk_mesh(&mesh.vertices, &indices, render_class, &label);
+                self.translucent = Some(translucent::TranslucentMesh {
+                    mesh: gpu_mesh.clone(),
+                    indices: mesh.indices,
+                    quads: mesh.quads,
+                    sorted_from: camera,
+                });
+                meshes.push(gpu_mesh);
+            } else {
+                meshes.push(ctx.create_chunk_mesh(
+                    &mesh.vertices,
+                    &mesh.indices,
+                    render_class,
+                    &label,
+                ));
+            }
+        }
+
+        let half_size = CHUNK_SIZE as f32 / 2.0;
+        let origin = self.position.origin();
+        let center = Point3::new(
+            origin.x as f32 + half_size,
+            origin.y as f32 + half_size,
+            origin.z as f32 + half_size,
+        );
+
+        self.mesh = Some(ctx.create_chunk_model(
+            meshes,
+            textures.material(),
+            [origin.x, origin.y, origin.z],
+            center,
+            &label,
+        ));
+    }
+}
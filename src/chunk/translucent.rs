@@ -0,0 +1,67 @@
+use cgmath::{InnerSpace, MetricSpace, Point3, Vector3};
+
+use super::Chunk;
+use crate::engine::{model::Mesh, object::Context};
+
+/// How far the camera moves before a chunk's translucent quads are sorted again.
+const RESORT_DISTANCE: f32 = 1.0;
+
+/// The uploaded translucent mesh of a chunk, with what is needed to redraw its
+/// quads back to front from wherever the camera is.
+pub(super) struct TranslucentMesh {
+    pub mesh: Mesh,
+    /// Indices in the order they were built.
+    pub indices: Vec<u32>,
+    /// See [`super::MeshData::quads`].
+    pub quads: Vec<(Point3<f32>, usize)>,
+    /// Camera position of the last sort.
+    pub sorted_from: Point3<f32>,
+}
+
+/// `indices` with the quads reordered from the furthest to the nearest to `eye`,
+/// given relative to the chunk origin.
+pub(super) fn back_to_front(
+    indices: &[u32],
+    quads: &[(Point3<f32>, usize)],
+    eye: Point3<f32>,
+) -> Vec<u32> {
+    let mut order: Vec<usize> = (0..quads.len()).collect();
+    order.sort_by(|&a, &b| {
+        let distance_a = (quads[a].0 - eye).magnitude2();
+        let distance_b = (quads[b].0 - eye).magnitude2();
+        distance_b.total_cmp(&distance_a)
+    });
+
+    let mut sorted = Vec::with_capacity(indices.len());
+    for quad in order {
+        let end = quads
+            .get(quad + 1)
+            .map_or(indices.len(), |&(_, start)| start);
+        sorted.extend_from_slice(&indices[quads[quad].1..end]);
+    }
+    sorted
+}
+
+impl Chunk {
+    /// The camera position relative to the chunk origin.
+    pub(super) fn local_eye(&self, camera: Point3<f32>) -> Point3<f32> {
+        let origin = self.position.origin();
+        camera - Vector3::new(origin.x as f32, origin.y as f32, origin.z as f32)
+    }
+
+    /// Sorts the translucent quads back to front again once the camera has moved
+    /// far enough from where they were last sorted.
+    pub fn sort_translucent(&mut self, camera: Point3<f32>, ctx: &Context) {
+        let eye = self.local_eye(camera);
+        let Some(translucent) = &mut self.translucent else {
+            return;
+        };
+        if translucent.sorted_from.distance2(camera) < RESORT_DISTANCE * RESORT_DISTANCE {
+            return;
+        }
+
+        let indices = back_to_front(&translucent.indices, &translucent.quads, eye);
+        ctx.write_mesh_indices(&translucent.mesh, &indices);
+        translucent.sorted_from = camera;
+    }
+}
@@ -0,0 +1,213 @@
+use cgmath::Point3;
+use serde::{Deserialize, Serialize};
+
+use crate::{blocks::BlockFace, chunk::CHUNK_SIZE};
+
+/// Position of a block in the world, in blocks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlockPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl BlockPos {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// The block containing a point in world space.
+    pub fn containing(point: Point3<f32>) -> Self {
+        Self::new(
+            point.x.floor() as i32,
+            point.y.floor() as i32,
+            point.z.floor() as i32,
+        )
+    }
+
+    /// The chunk containing this block, rounding towards negative infinity.
+    pub fn chunk(&self) -> ChunkPosition {
+        let size = CHUNK_SIZE as i32;
+        ChunkPosition::new(
+            self.x.div_euclid(size),
+            self.y.div_euclid(size),
+            self.z.div_euclid(size),
+        )
+    }
+
+    /// Position inside the containing chunk.
+    pub fn local(&self) -> LocalPos {
+        let size = CHUNK_SIZE as i32;
+        LocalPos::new(
+            self.x.rem_euclid(size) as usize,
+            self.y.rem_euclid(size) as usize,
+            self.z.rem_euclid(size) as usize,
+        )
+    }
+
+    pub fn offset(&self, dx: i32, dy: i32, dz: i32) -> Self {
+        Self::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+
+    /// The block sharing `face` with this one.
+    pub fn neighbor(&self, face: BlockFace) -> Self {
+        let [dx, dy, dz] = face.offset();
+        self.offset(dx, dy, dz)
+    }
+
+    pub fn above(&self) -> Self {
+        self.offset(0, 1, 0)
+    }
+
+    pub fn below(&self) -> Self {
+        self.offset(0, -1, 0)
+    }
+}
+
+/// Position of a block inside its chunk; every axis is in `0..CHUNK_SIZE`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LocalPos {
+    pub x: usize,
+    pub y: usize,
+    pub z: usize,
+}
+
+impl LocalPos {
+    pub fn new(x: usize, y: usize, z: usize) -> Self {
+        debug_assert!(
+            x < CHUNK_SIZE && y < CHUNK_SIZE && z < CHUNK_SIZE,
+            "Local position ({x}, {y}, {z}) is outside the chunk"
+        );
+        Self { x, y, z }
+    }
+
+    /// Index of the cell in chunk storage, laid out as y, then z, then x.
+    pub fn index(&self) -> usize {
+        (self.y * CHUNK_SIZE + self.z) * CHUNK_SIZE + self.x
+    }
+
+    /// Every cell of a chunk, in storage order.
+    pub fn all() -> impl Iterator<Item = LocalPos> {
+        (0..CHUNK_SIZE).flat_map(Self::layer)
+    }
+
+    /// Every cell of the horizontal layer at height `y`, in storage order.
+    pub fn layer(y: usize) -> impl Iterator<Item = LocalPos> {
+        (0..CHUNK_SIZE).flat_map(move |z| (0..CHUNK_SIZE).map(move |x| Self::new(x, y, z)))
+    }
+
+    /// Every cell of the column at `x`, `z`, bottom to top.
+    pub fn column(x: usize, z: usize) -> impl DoubleEndedIterator<Item = LocalPos> {
+        (0..CHUNK_SIZE).map(move |y| Self::new(x, y, z))
+    }
+}
+
+/// Position of a chunk in the world, in chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkPosition {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl ChunkPosition {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// The chunk sharing `face` with this one.
+    pub fn neighbor(&self, face: BlockFace) -> Self {
+        let [dx, dy, dz] = face.offset();
+        Self::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+
+    /// The six chunks sharing a face with this one.
+    pub fn neighbors(&self) -> [ChunkPosition; 6] {
+        BlockFace::ALL.map(|face| self.neighbor(face))
+    }
+
+    /// The 26 chunks sharing a face, edge or corner with this one.
+    pub fn surrounding(&self) -> impl Iterator<Item = ChunkPosition> {
+        let center = *self;
+        (-1..=1)
+            .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+            .filter(|&offset| offset != (0, 0, 0))
+            .map(move |(dx, dy, dz)| Self::new(center.x + dx, center.y + dy, center.z + dz))
+    }
+
+    /// Horizontal position of the column of chunks this one belongs to.
+    pub fn column(&self) -> (i32, i32) {
+        (self.x, self.z)
+    }
+
+    /// World position of the chunk's lowest corner block.
+    pub fn origin(&self) -> BlockPos {
+        let size = CHUNK_SIZE as i32;
+        BlockPos::new(self.x * size, self.y * size, self.z * size)
+    }
+
+    /// World position of a block of this chunk.
+    pub fn block_pos(&self, local: LocalPos) -> BlockPos {
+        self.origin()
+            .offset(local.x as i32, local.y as i32, local.z as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// World coordinates around chunk borders, with the chunk and local
+    /// coordinate each falls in.
+    const CASES: [(i32, i32, usize); 7] = [
+        (0, 0, 0),
+        (15, 0, 15),
+        (16, 1, 0),
+        (-1, -1, 15),
+        (-16, -1, 0),
+        (-17, -2, 15),
+        (i32::MIN, i32::MIN / 16, 0),
+    ];
+
+    #[test]
+    fn block_positions_floor_into_chunks() {
+        for (world, chunk, local) in CASES {
+            let pos = BlockPos::new(world, world, world);
+            assert_eq!(
+                pos.chunk(),
+                ChunkPosition::new(chunk, chunk, chunk),
+                "{}",
+                world
+            );
+            assert_eq!(pos.local(), LocalPos::new(local, local, local), "{}", world);
+        }
+    }
+
+    #[test]
+    fn chunk_origins_are_their_lowest_block() {
+        for (world, chunk, local) in CASES {
+            let origin = ChunkPosition::new(chunk, chunk, chunk).origin();
+            let expected = world - local as i32;
+            assert_eq!(
+                origin,
+                BlockPos::new(expected, expected, expected),
+                "{}",
+                world
+            );
+        }
+    }
+
+    #[test]
+    fn block_positions_round_trip_through_chunks() {
+        for (world, _, _) in CASES {
+            for pos in [
+                BlockPos::new(world, 0, 0),
+                BlockPos::new(0, world, 0),
+                BlockPos::new(0, 0, world),
+                BlockPos::new(world, world, world),
+            ] {
+                assert_eq!(pos.chunk().block_pos(pos.local()), pos);
+            }
+        }
+    }
+}
@@ -131,6 +131,23 @@ impl ApplicationHandler for App {
         }
     }
 
+    fn exiting(&mut self, _event_loop: &event_loop::ActiveEventLoop) {
+        let (Some(renderer_state), Some(window)) =
+            (self.renderer_state.as_mut(), self.window.as_mut())
+        else {
+            return;
+        };
+        let mut ctx = Context {
+            renderer_state,
+            window,
+            input: &mut self.input,
+        };
+
+        for object in &mut self.objects {
+            object.destroy(&mut ctx);
+        }
+    }
+
     fn device_event(
         &mut self,
         _event_loop: &event_loop::ActiveEventLoop,
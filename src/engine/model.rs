@@ -1,4 +1,10 @@
-use std::ops::Range;
+use std::{
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+
+use cgmath::Point3;
+use serde::Deserialize;
 
 pub(in crate::engine) trait Vertex {
     fn desc() -> wgpu::VertexBufferLayout<'static>;
@@ -11,6 +17,8 @@ pub struct ModelVertex {
     pub text_coords: [f32; 2],
     pub normal: [f32; 3],
     pub tex_index: u32,
+    /// Ambient occlusion, from 0.0 for fully occluded to 1.0 for open.
+    pub ao: f32,
 }
 
 impl Vertex for ModelVertex {
@@ -41,11 +49,108 @@ impl Vertex for ModelVertex {
                     offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
                     shader_location: 3,
                 },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32,
+                    offset: mem::size_of::<[f32; 9]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                },
             ],
         }
     }
 }
 
+/// Compact vertex of chunk meshes, two words:
+///
+/// | word | bits | field |
+/// |------|------|-------|
+/// | 0 | 0-26 | position relative to the chunk origin, 9 bits per axis, in sixteenths of a block |
+/// | 0 | 27-29 | face, in `BlockFace` order, or [`ChunkVertex::DIAGONAL`] |
+/// | 0 | 30-31 | corner of the quad, in `BlockFace::get_box_vertices` order |
+/// | 1 | 0-15 | texture index |
+/// | 1 | 16-17 | ambient occlusion level, from 0 fully occluded to 3 open |
+/// | 1 | 18-21 | light level |
+/// | 1 | 22-24 | texture transform, see [`ChunkVertex::new`] |
+///
+/// Texture coordinates are derived from the position and face in `vs_chunk`,
+/// repeating once per block, and from the corner on diagonal quads.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ChunkVertex {
+    data: [u32; 2],
+}
+
+impl ChunkVertex {
+    /// Face id of the diagonal quads of cross-shaped blocks.
+    pub const DIAGONAL: u32 = 6;
+    pub const MAX_LIGHT: u32 = 15;
+
+    /// `tex_transform` turns the face's texture: bit 0 swaps u and v, then bits 1
+    /// and 2 mirror u and v.
+    pub fn new(
+        position: [u32; 3],
+        face: u32,
+        corner: u32,
+        tex_index: u32,
+        ao: u32,
+        light: u32,
+        tex_transform: u32,
+    ) -> Self {
+        debug_assert!(position.iter().all(|&v| v < 1 << 9));
+        debug_assert!(face <= Self::DIAGONAL && corner < 4);
+        debug_assert!(tex_index < 1 << 16 && ao < 4 && light <= Self::MAX_LIGHT);
+        debug_assert!(tex_transform < 8);
+
+        Self {
+            data: [
+                position[0] | position[1] << 9 | position[2] << 18 | face << 27 | corner << 30,
+                tex_index | ao << 16 | light << 18 | tex_transform << 22,
+            ],
+        }
+    }
+}
+
+impl Vertex for ChunkVertex {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ChunkVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Uint32x2,
+                offset: 0,
+                shader_location: 0,
+            }],
+        }
+    }
+}
+
+/// Per-draw values of a chunk model.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub(in crate::engine) struct ChunkUniform {
+    pub origin: [i32; 3],
+    pub _padding: i32,
+}
+
+/// How a mesh is blended into the frame, which also decides when it is drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum RenderClass {
+    /// Fully opaque, drawn first.
+    #[default]
+    Opaque,
+    /// Opaque with fully transparent holes, drawn with an alpha test.
+    Cutout,
+    /// Partially transparent, blended and drawn back to front after everything else.
+    Translucent,
+}
+
+impl RenderClass {
+    pub const ALL: [RenderClass; 3] = [
+        RenderClass::Opaque,
+        RenderClass::Cutout,
+        RenderClass::Translucent,
+    ];
+}
+
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Material {
     pub(in crate::engine) bind_group: wgpu::BindGroup,
@@ -57,12 +162,41 @@ pub struct Mesh {
     pub(in crate::engine) index_buffer: wgpu::Buffer,
     pub(in crate::engine) num_elements: u32,
     pub material: usize,
+    pub render_class: RenderClass,
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone)]
 pub struct Model {
     pub meshes: Vec<Mesh>,
     pub materials: Vec<Material>,
+    /// World-space center, used to order translucent models by distance.
+    pub center: Point3<f32>,
+    /// Bind group of the [`ChunkUniform`] of chunk models, which are drawn with
+    /// the chunk pipelines.
+    pub(in crate::engine) chunk: Option<wgpu::BindGroup>,
+}
+
+impl Model {
+    pub(in crate::engine) fn has_render_class(&self, render_class: RenderClass) -> bool {
+        self.meshes
+            .iter()
+            .any(|mesh| mesh.render_class == render_class)
+    }
+}
+
+impl PartialEq for Model {
+    fn eq(&self, other: &Self) -> bool {
+        self.meshes == other.meshes && self.materials == other.materials
+    }
+}
+
+impl Eq for Model {}
+
+impl Hash for Model {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.meshes.hash(state);
+        self.materials.hash(state);
+    }
 }
 
 pub(in crate::engine) trait DrawModel<'a> {
@@ -73,10 +207,16 @@ pub(in crate::engine) trait DrawModel<'a> {
         camera_bind_group: &'a wgpu::BindGroup,
         instances: Range<u32>,
     );
-    fn draw_model(&mut self, model: &'a Model, camera_bind_group: &'a wgpu::BindGroup);
+    fn draw_model(
+        &mut self,
+        model: &'a Model,
+        render_class: RenderClass,
+        camera_bind_group: &'a wgpu::BindGroup,
+    );
     fn draw_model_instanced(
         &mut self,
         model: &'a Model,
+        render_class: RenderClass,
         camera_bind_group: &'a wgpu::BindGroup,
         instances: Range<u32>,
     );
@@ -100,17 +240,30 @@ where
         self.draw_indexed(0..mesh.num_elements, 0, instances);
     }
 
-    fn draw_model(&mut self, model: &'b Model, camera_bind_group: &'b wgpu::BindGroup) {
-        self.draw_model_instanced(model, camera_bind_group, 0..1);
+    fn draw_model(
+        &mut self,
+        model: &'b Model,
+        render_class: RenderClass,
+        camera_bind_group: &'b wgpu::BindGroup,
+    ) {
+        self.draw_model_instanced(model, render_class, camera_bind_group, 0..1);
     }
 
     fn draw_model_instanced(
         &mut self,
         model: &'b Model,
+        render_class: RenderClass,
         camera_bind_group: &'b wgpu::BindGroup,
         instances: Range<u32>,
     ) {
-        for mesh in &model.meshes {
+        if let Some(chunk_bind_group) = &model.chunk {
+            self.set_bind_group(2, chunk_bind_group, &[]);
+        }
+        for mesh in model
+            .meshes
+            .iter()
+            .filter(|mesh| mesh.render_class == render_class)
+        {
             let material = &model.materials[mesh.material];
             self.draw_mesh_instanced(mesh, material, camera_bind_group, instances.clone());
         }
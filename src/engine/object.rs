@@ -27,6 +27,10 @@ impl<'a> Context<'a> {
     pub fn get_camera_position(&mut self) -> Point3<f32> {
         self.renderer_state.camera.position
     }
+
+    pub fn get_camera(&self) -> Camera {
+        self.renderer_state.camera
+    }
 }
 
 #[allow(unused)]
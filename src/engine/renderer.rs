@@ -1,4 +1,6 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{collections::HashSet, sync::Arc, time::Instant};
+
+use cgmath::InnerSpace;
 
 use wgpu::{
     Adapter, BindGroup, BindGroupLayout, Buffer, Device, Instance, Queue, RenderPipeline,
@@ -8,7 +10,7 @@ use winit::{dpi::PhysicalSize, window::Window};
 
 use super::{
     camera::{self, CameraUniform},
-    model::{self, DrawModel, ModelVertex, Vertex},
+    model::{self, ChunkVertex, DrawModel, ModelVertex, RenderClass, Vertex},
     texture,
 };
 
@@ -18,15 +20,22 @@ pub struct RendererState {
     pub queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
     pub size: winit::dpi::PhysicalSize<u32>,
-    render_pipeline: wgpu::RenderPipeline,
+    opaque_pipeline: wgpu::RenderPipeline,
+    cutout_pipeline: wgpu::RenderPipeline,
+    translucent_pipeline: wgpu::RenderPipeline,
+    /// Pipelines of chunk models, in [`RenderClass::ALL`] order.
+    chunk_pipelines: [wgpu::RenderPipeline; 3],
 
     pub camera: camera::Camera,
     projection: camera::Projection,
     camera_uniform: camera::CameraUniform,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
+    globals_buffer: wgpu::Buffer,
+    start_time: Instant,
 
     pub diffuse_bind_group_layout: wgpu::BindGroupLayout,
+    pub chunk_bind_group_layout: wgpu::BindGroupLayout,
     deth_texture: texture::Texture,
 
     pub models: HashSet<model::Model>,
@@ -44,13 +53,32 @@ impl RendererState {
         let shader_module = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
         let diffuse_bind_group_layout = Self::create_diffuse_bind_group_layout(&device);
         let camera_bind_group_layout = Self::create_camera_bind_group_layout(&device);
-        let render_pipeline = Self::create_render_pipeline(
-            &device,
-            &config,
-            shader_module,
-            &diffuse_bind_group_layout,
-            &camera_bind_group_layout,
-        );
+        let chunk_bind_group_layout = Self::create_chunk_bind_group_layout(&device);
+        let [opaque_pipeline, cutout_pipeline, translucent_pipeline] =
+            RenderClass::ALL.map(|render_class| {
+                Self::create_render_pipeline(
+                    &device,
+                    &config,
+                    &shader_module,
+                    &[&diffuse_bind_group_layout, &camera_bind_group_layout],
+                    ("vs_main", ModelVertex::desc()),
+                    render_class,
+                )
+            });
+        let chunk_pipelines = RenderClass::ALL.map(|render_class| {
+            Self::create_render_pipeline(
+                &device,
+                &config,
+                &shader_module,
+                &[
+                    &diffuse_bind_group_layout,
+                    &camera_bind_group_layout,
+                    &chunk_bind_group_layout,
+                ],
+                ("vs_chunk", ChunkVertex::desc()),
+                render_class,
+            )
+        });
 
         let deth_texture = texture::Texture::create_deth_texture(&device, &config, "deth_texture");
 
@@ -60,8 +88,13 @@ impl RendererState {
         let mut camera_uniform = camera::CameraUniform::new();
         camera_uniform.update_view_proj(&camera, &projection);
         let camera_buffer = Self::create_camera_buffer(&device, &camera_uniform);
-        let camera_bind_group =
-            Self::create_camera_bind_buffer(&device, &camera_buffer, &camera_bind_group_layout);
+        let globals_buffer = Self::create_globals_buffer(&device);
+        let camera_bind_group = Self::create_camera_bind_buffer(
+            &device,
+            &camera_buffer,
+            &globals_buffer,
+            &camera_bind_group_layout,
+        );
 
         Self {
             surface,
@@ -69,13 +102,19 @@ impl RendererState {
             queue,
             config,
             size,
-            render_pipeline,
+            opaque_pipeline,
+            cutout_pipeline,
+            translucent_pipeline,
+            chunk_pipelines,
             camera,
             projection,
             camera_uniform,
             camera_buffer,
             camera_bind_group,
+            globals_buffer,
+            start_time: Instant::now(),
             diffuse_bind_group_layout,
+            chunk_bind_group_layout,
             deth_texture,
             models: HashSet::new(),
         }
@@ -103,6 +142,13 @@ impl RendererState {
             0,
             bytemuck::cast_slice(&[self.camera_uniform]),
         );
+
+        let globals = GlobalsUniform {
+            time: self.start_time.elapsed().as_secs_f32(),
+            _padding: [0.0; 3],
+        };
+        self.queue
+            .write_buffer(&self.globals_buffer, 0, bytemuck::cast_slice(&[globals]));
     }
 
     pub fn render(&mut self) -> anyhow::Result<(), wgpu::SurfaceError> {
@@ -144,10 +190,22 @@ impl RendererState {
                 occlusion_query_set: None,
             });
 
-            render_pass.set_pipeline(&self.render_pipeline);
-            for model in &self.models {
-                render_pass.draw_model(model, &self.camera_bind_group);
+            // Front to back, so nearer geometry hides what is behind it before it is shaded.
+            let camera_position = self.camera.position;
+            let mut models: Vec<&model::Model> = self.models.iter().collect();
+            models.sort_by(|a, b| {
+                let distance_a = (a.center - camera_position).magnitude2();
+                let distance_b = (b.center - camera_position).magnitude2();
+                distance_a.total_cmp(&distance_b)
+            });
+
+            for render_class in [RenderClass::Opaque, RenderClass::Cutout] {
+                self.draw_models(&mut render_pass, &models, render_class);
             }
+            // Translucent meshes blend over what is behind them, so they go last,
+            // back to front.
+            models.reverse();
+            self.draw_models(&mut render_pass, &models, RenderClass::Translucent);
         }
 
         self.queue.submit(std::iter::once(encoder.finish()));
@@ -156,6 +214,37 @@ impl RendererState {
         Ok(())
     }
 
+    /// Draws the meshes of `render_class` of `models`, in order.
+    fn draw_models<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        models: &[&'a model::Model],
+        render_class: RenderClass,
+    ) {
+        let mut chunk_pipeline = None;
+        for model in models
+            .iter()
+            .filter(|model| model.has_render_class(render_class))
+        {
+            let chunk = model.chunk.is_some();
+            if chunk_pipeline != Some(chunk) {
+                render_pass.set_pipeline(self.pipeline(render_class, chunk));
+                chunk_pipeline = Some(chunk);
+            }
+            render_pass.draw_model(model, render_class, &self.camera_bind_group);
+        }
+    }
+
+    /// The pipeline drawing meshes of `render_class`, of chunk models or others.
+    fn pipeline(&self, render_class: RenderClass, chunk: bool) -> &RenderPipeline {
+        match (render_class, chunk) {
+            (render_class, true) => &self.chunk_pipelines[render_class as usize],
+            (RenderClass::Opaque, false) => &self.opaque_pipeline,
+            (RenderClass::Cutout, false) => &self.cutout_pipeline,
+            (RenderClass::Translucent, false) => &self.translucent_pipeline,
+        }
+    }
+
     fn create_gpu_intance() -> Instance {
         Instance::new(&wgpu::InstanceDescriptor {
             backends: wgpu::Backends::PRIMARY,
@@ -213,24 +302,46 @@ impl RendererState {
     fn create_render_pipeline(
         device: &Device,
         config: &SurfaceConfiguration,
-        shader_module: ShaderModule,
-        diffuse_bind_group_layout: &BindGroupLayout,
-        camera_bind_group_layout: &BindGroupLayout,
+        shader_module: &ShaderModule,
+        bind_group_layouts: &[&BindGroupLayout],
+        (vertex_entry, vertex_layout): (&str, wgpu::VertexBufferLayout),
+        render_class: RenderClass,
     ) -> RenderPipeline {
+        let (label, fragment_entry, blend, depth_write_enabled) = match render_class {
+            RenderClass::Opaque => (
+                "Opaque Render Pipeline",
+                "fs_main",
+                wgpu::BlendState::REPLACE,
+                true,
+            ),
+            RenderClass::Cutout => (
+                "Cutout Render Pipeline",
+                "fs_cutout",
+                wgpu::BlendState::REPLACE,
+                true,
+            ),
+            RenderClass::Translucent => (
+                "Translucent Render Pipeline",
+                "fs_main",
+                wgpu::BlendState::ALPHA_BLENDING,
+                false,
+            ),
+        };
+
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[diffuse_bind_group_layout, camera_bind_group_layout],
+            bind_group_layouts,
             push_constant_ranges: &[],
         });
 
         device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
+            label: Some(&format!("{} ({})", label, vertex_entry)),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &shader_module,
-                entry_point: Some("vs_main"),
+                module: shader_module,
+                entry_point: Some(vertex_entry),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                buffers: &[ModelVertex::desc()],
+                buffers: &[vertex_layout],
             },
             primitive: wgpu::PrimitiveState {
                 topology: wgpu::PrimitiveTopology::TriangleList,
@@ -243,7 +354,7 @@ impl RendererState {
             },
             depth_stencil: Some(wgpu::DepthStencilState {
                 format: texture::Texture::DETH_FORMAT,
-                depth_write_enabled: true,
+                depth_write_enabled,
                 depth_compare: wgpu::CompareFunction::Less,
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
@@ -254,12 +365,12 @@ impl RendererState {
                 alpha_to_coverage_enabled: false,
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader_module,
-                entry_point: Some("fs_main"),
+                module: shader_module,
+                entry_point: Some(fragment_entry),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
                 targets: &[Some(wgpu::ColorTargetState {
                     format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
+                    blend: Some(blend),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
@@ -288,10 +399,46 @@ impl RendererState {
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
         })
     }
 
+    fn create_chunk_bind_group_layout(device: &Device) -> BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Chunk Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        })
+    }
+
     fn create_camera_buffer(device: &Device, camera_uniform: &CameraUniform) -> Buffer {
         device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Camera Buffer"),
@@ -300,34 +447,73 @@ impl RendererState {
         })
     }
 
+    fn create_globals_buffer(device: &Device) -> Buffer {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Globals Buffer"),
+            contents: bytemuck::cast_slice(&[GlobalsUniform {
+                time: 0.0,
+                _padding: [0.0; 3],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        })
+    }
+
     fn create_camera_bind_group_layout(device: &Device) -> BindGroupLayout {
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Camera Bind Group Layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
                 },
-                count: None,
-            }],
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
         })
     }
 
     fn create_camera_bind_buffer(
         device: &Device,
         camera_buffer: &Buffer,
+        globals_buffer: &Buffer,
         camera_bind_group_layout: &BindGroupLayout,
     ) -> BindGroup {
         device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Camera Bind Group"),
             layout: camera_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: globals_buffer.as_entire_binding(),
+                },
+            ],
         })
     }
 }
+
+/// Per-frame values shared by every shader, such as the time animations run on.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GlobalsUniform {
+    /// Seconds since the renderer started.
+    time: f32,
+    _padding: [f32; 3],
+}
@@ -1,4 +1,5 @@
 use anyhow::Ok;
+use cgmath::Point3;
 use wgpu::util::DeviceExt;
 
 use super::{model, object::Context, texture};
@@ -12,22 +13,100 @@ fn load_binary(file_name: &str) -> anyhow::Result<Vec<u8>> {
     Ok(data)
 }
 
+pub fn load_string(file_name: &str) -> anyhow::Result<String> {
+    let data = load_binary(file_name)?;
+
+    Ok(String::from_utf8(data)?)
+}
+
+fn resource_exists(file_name: &str) -> bool {
+    std::path::Path::new(env!("OUT_DIR"))
+        .join("res")
+        .join(file_name)
+        .is_file()
+}
+
 impl<'a> Context<'a> {
-    pub fn load_texture_array(&self, file_names: &[&str]) -> anyhow::Result<texture::Texture> {
+    /// Loads `<name>.png` for every name into one texture array, one layer per frame.
+    /// A texture with a `<name>.anim.ron` beside it is an animation strip.
+    pub fn load_texture_array(&self, names: &[&str]) -> anyhow::Result<texture::TextureArray> {
         let mut images = Vec::new();
+        let mut animations = Vec::new();
+        let mut frames = Vec::new();
 
-        for file_name in file_names {
-            let data = load_binary(file_name)?;
+        for name in names {
+            let data = load_binary(&format!("{}.png", name))?;
             let img = image::load_from_memory(&data)?;
-            images.push(img);
+
+            let metadata_file = format!("{}.anim.ron", name);
+            let animation = if resource_exists(&metadata_file) {
+                let animation: texture::Animation = ron::from_str(&load_string(&metadata_file)?)
+                    .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", metadata_file, e))?;
+                Some(animation)
+            } else {
+                None
+            };
+
+            let first_layer = images.len() as u32;
+            let first_frame = frames.len() as u32;
+            let frame_time = match animation {
+                Some(animation) => {
+                    if animation.frame_time <= 0.0 {
+                        return Err(anyhow::anyhow!(
+                            "Animated texture {} needs a positive frame time",
+                            name
+                        ));
+                    }
+
+                    let size = img.width();
+                    if size == 0 || img.height() % size != 0 {
+                        return Err(anyhow::anyhow!(
+                            "Animated texture {} must be a vertical strip of square frames",
+                            name
+                        ));
+                    }
+
+                    let strip_len = img.height() / size;
+                    for frame in 0..strip_len {
+                        images.push(img.crop_imm(0, frame * size, size, size));
+                    }
+
+                    if animation.frames.is_empty() {
+                        frames.extend(first_layer..first_layer + strip_len);
+                    } else {
+                        for &frame in &animation.frames {
+                            if frame >= strip_len {
+                                return Err(anyhow::anyhow!(
+                                    "Animated texture {} has no frame {}",
+                                    name,
+                                    frame
+                                ));
+                            }
+                            frames.push(first_layer + frame);
+                        }
+                    }
+                    animation.frame_time
+                }
+                None => {
+                    images.push(img);
+                    frames.push(first_layer);
+                    0.0
+                }
+            };
+
+            animations.push(texture::TextureAnimation {
+                first_frame,
+                frame_count: frames.len() as u32 - first_frame,
+                frame_time,
+                _padding: 0,
+            });
         }
 
         let (width, height) = (images[0].width(), images[0].height());
-        for (i, img) in images.iter().enumerate() {
+        for img in &images {
             if img.width() != width || img.height() != height {
                 return Err(anyhow::anyhow!(
-                    "Texture {} has different dimensions ({}, {}) than first texture ({}, {})",
-                    file_names[i],
+                    "Texture frame has different dimensions ({}, {}) than the first texture ({}, {})",
                     img.width(),
                     img.height(),
                     width,
@@ -36,21 +115,102 @@ impl<'a> Context<'a> {
             }
         }
 
-        Ok(texture::Texture::from_image_array(
-            &self.renderer_state.device,
+        let device = &self.renderer_state.device;
+        let label = format!("texture_array_{}", names.join("_"));
+        let texture = texture::Texture::from_image_array(
+            device,
             &self.renderer_state.queue,
             &images,
-            Some(&format!("texture_array_{}", file_names.join("_"))),
-        ))
+            Some(&label),
+        );
+        let indices = names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.to_string(), index as u32))
+            .collect();
+        let animations = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(format!("{} Animations", label).as_str()),
+            contents: bytemuck::cast_slice(&animations),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let frames = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(format!("{} Frames", label).as_str()),
+            contents: bytemuck::cast_slice(&frames),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+
+        Ok(texture::TextureArray {
+            texture,
+            indices,
+            animations,
+            frames,
+        })
     }
 
-    pub fn create_model(
+    pub fn create_material(
+        &self,
+        textures: &texture::TextureArray,
+        label: &str,
+    ) -> model::Material {
+        let texture = &textures.texture;
+        let bind_group = self
+            .renderer_state
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(format!("{} Texture Bind Group", label).as_str()),
+                layout: &self.renderer_state.diffuse_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&texture.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: textures.animations.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: textures.frames.as_entire_binding(),
+                    },
+                ],
+            });
+
+        model::Material { bind_group }
+    }
+
+    #[allow(dead_code)]
+    pub fn create_mesh(
         &self,
         vertices: &[model::ModelVertex],
         indices: &[u32],
-        texture: texture::Texture,
+        render_class: model::RenderClass,
+        label: &str,
+    ) -> model::Mesh {
+        self.create_mesh_buffers(vertices, indices, render_class, label)
+    }
+
+    /// Creates a mesh for a model made by [`Context::create_chunk_model`].
+    pub fn create_chunk_mesh(
+        &self,
+        vertices: &[model::ChunkVertex],
+        indices: &[u32],
+        render_class: model::RenderClass,
         label: &str,
-    ) -> anyhow::Result<model::Model> {
+    ) -> model::Mesh {
+        self.create_mesh_buffers(vertices, indices, render_class, label)
+    }
+
+    fn create_mesh_buffers<V: bytemuck::Pod>(
+        &self,
+        vertices: &[V],
+        indices: &[u32],
+        render_class: model::RenderClass,
+        label: &str,
+    ) -> model::Mesh {
         let device = &self.renderer_state.device;
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -61,36 +221,74 @@ impl<'a> Context<'a> {
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some(format!("{} Index Buffer", label).as_str()),
             contents: bytemuck::cast_slice(indices),
-            usage: wgpu::BufferUsages::INDEX,
-        });
-
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some(format!("{} Texture Bind Group", label).as_str()),
-            layout: &self.renderer_state.diffuse_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&texture.view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&texture.sampler),
-                },
-            ],
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
         });
 
-        let material = model::Material { bind_group };
-
-        let mesh = model::Mesh {
+        model::Mesh {
             vertex_buffer,
             index_buffer,
             num_elements: indices.len() as u32,
             material: 0,
-        };
+            render_class,
+        }
+    }
 
-        Ok(model::Model {
-            meshes: vec![mesh],
-            materials: vec![material],
-        })
+    /// Replaces the indices of a mesh with as many new ones, such as the same
+    /// triangles in another order.
+    pub fn write_mesh_indices(&self, mesh: &model::Mesh, indices: &[u32]) {
+        debug_assert_eq!(indices.len(), mesh.num_elements as usize);
+        self.renderer_state.queue.write_buffer(
+            &mesh.index_buffer,
+            0,
+            bytemuck::cast_slice(indices),
+        );
+    }
+
+    pub fn create_model(
+        &self,
+        meshes: Vec<model::Mesh>,
+        material: &model::Material,
+        center: Point3<f32>,
+    ) -> model::Model {
+        model::Model {
+            meshes,
+            materials: vec![material.clone()],
+            center,
+            chunk: None,
+        }
+    }
+
+    /// Creates a model of [`model::ChunkVertex`] meshes, whose positions are
+    /// relative to `origin`.
+    pub fn create_chunk_model(
+        &self,
+        meshes: Vec<model::Mesh>,
+        material: &model::Material,
+        origin: [i32; 3],
+        center: Point3<f32>,
+        label: &str,
+    ) -> model::Model {
+        let device = &self.renderer_state.device;
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(format!("{} Uniform Buffer", label).as_str()),
+            contents: bytemuck::cast_slice(&[model::ChunkUniform {
+                origin,
+                _padding: 0,
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(format!("{} Bind Group", label).as_str()),
+            layout: &self.renderer_state.chunk_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        model::Model {
+            chunk: Some(bind_group),
+            ..self.create_model(meshes, material, center)
+        }
     }
 }
@@ -5,16 +5,26 @@ struct CameraUniform {
 @group(1) @binding(0)
 var<uniform> camera: CameraUniform;
 
+struct Globals {
+    time: f32,
+}
+@group(1) @binding(1)
+var<uniform> globals: Globals;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
     @location(3) tex_index: u32,
+    @location(4) ao: f32,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
     @location(1) @interpolate(flat) tex_index: u32,
+    @location(2) ao: f32,
+    // Brightness from 0.0 to 1.0.
+    @location(3) light: f32,
 };
 
 @vertex
@@ -23,6 +33,73 @@ fn vs_main(model: VertexInput) -> VertexOutput {
     out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
     out.tex_coords = model.tex_coords;
     out.tex_index = model.tex_index;
+    out.ao = model.ao;
+    out.light = 1.0;
+    return out;
+}
+
+struct ChunkUniform {
+    origin: vec3<i32>,
+}
+@group(2) @binding(0)
+var<uniform> chunk: ChunkUniform;
+
+// Face id of the diagonal quads of cross-shaped blocks.
+const FACE_DIAGONAL: u32 = 6u;
+const MAX_LIGHT: f32 = 15.0;
+
+// Unrotated texture coordinates at a position on a block face, in blocks; they
+// repeat once per block, so merged faces tile their texture.
+fn face_tex_coords(face: u32, p: vec3<f32>) -> vec2<f32> {
+    switch face {
+        case 0u: { return vec2<f32>(p.x, 1.0 - p.y); }
+        case 1u: { return vec2<f32>(1.0 - p.x, 1.0 - p.y); }
+        case 2u: { return vec2<f32>(p.z, 1.0 - p.y); }
+        case 3u: { return vec2<f32>(1.0 - p.z, 1.0 - p.y); }
+        case 4u: { return vec2<f32>(p.x, p.z); }
+        default: { return vec2<f32>(p.x, 1.0 - p.z); }
+    }
+}
+
+// Unpacks a `ChunkVertex`; see its documentation for the layout.
+@vertex
+fn vs_chunk(@location(0) data: vec2<u32>) -> VertexOutput {
+    let local = vec3<f32>(
+        f32(data.x & 0x1ffu),
+        f32((data.x >> 9u) & 0x1ffu),
+        f32((data.x >> 18u) & 0x1ffu),
+    ) / 16.0;
+    let face = (data.x >> 27u) & 0x7u;
+    let corner = data.x >> 30u;
+    let ao = (data.y >> 16u) & 0x3u;
+    let light = (data.y >> 18u) & 0xfu;
+    let tex_transform = (data.y >> 22u) & 0x7u;
+
+    var tex_coords: vec2<f32>;
+    if face == FACE_DIAGONAL {
+        tex_coords = vec2<f32>(f32(corner == 1u || corner == 2u), f32(corner <= 1u));
+    } else {
+        tex_coords = face_tex_coords(face, local);
+    }
+    // Turn the texture about the centre of its block.
+    tex_coords -= 0.5;
+    if (tex_transform & 1u) != 0u {
+        tex_coords = tex_coords.yx;
+    }
+    if (tex_transform & 2u) != 0u {
+        tex_coords.x = -tex_coords.x;
+    }
+    if (tex_transform & 4u) != 0u {
+        tex_coords.y = -tex_coords.y;
+    }
+
+    var out: VertexOutput;
+    let position = vec3<f32>(chunk.origin) + local;
+    out.clip_position = camera.view_proj * vec4<f32>(position, 1.0);
+    out.tex_coords = tex_coords + 0.5;
+    out.tex_index = data.y & 0xffffu;
+    out.ao = f32(ao) / 3.0;
+    out.light = f32(light) / MAX_LIGHT;
     return out;
 }
 
@@ -31,7 +108,46 @@ var t_diffuse: texture_2d_array<f32>;
 @group(0) @binding(1)
 var s_diffuse: sampler;
 
+struct TextureAnimation {
+    first_frame: u32,
+    frame_count: u32,
+    frame_time: f32,
+    _padding: u32,
+}
+@group(0) @binding(2)
+var<storage, read> animations: array<TextureAnimation>;
+// Array layer of every animation frame.
+@group(0) @binding(3)
+var<storage, read> frames: array<u32>;
+
+// The array layer showing the current frame of a texture.
+fn texture_layer(tex_index: u32) -> i32 {
+    let animation = animations[tex_index];
+    var frame = 0u;
+    if animation.frame_count > 1u {
+        frame = u32(globals.time / animation.frame_time) % animation.frame_count;
+    }
+    return i32(frames[animation.first_frame + frame]);
+}
+
+// Brightness of a fully occluded corner.
+const AO_MIN: f32 = 0.45;
+
+fn shade(color: vec4<f32>, in: VertexOutput) -> vec4<f32> {
+    return vec4<f32>(color.rgb * mix(AO_MIN, 1.0, in.ao) * in.light, color.a);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords, i32(in.tex_index));
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords, texture_layer(in.tex_index));
+    return shade(color, in);
+}
+
+@fragment
+fn fs_cutout(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords, texture_layer(in.tex_index));
+    if color.a < 0.5 {
+        discard;
+    }
+    return shade(color, in);
 }
@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
 #[derive(Clone)]
 pub struct Texture {
     pub(in crate::engine) view: wgpu::TextureView,
@@ -108,9 +112,10 @@ impl Texture {
             array_layer_count: Some(array_layers),
             ..Default::default()
         });
+        // Repeat addressing tiles the texture across quads merged from several blocks.
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
             address_mode_w: wgpu::AddressMode::ClampToEdge,
             mag_filter: wgpu::FilterMode::Nearest,
             min_filter: wgpu::FilterMode::Nearest,
@@ -121,3 +126,43 @@ impl Texture {
         Self { view, sampler }
     }
 }
+
+/// Animation metadata of a texture, read from `<name>.anim.ron` beside its PNG.
+/// The PNG is then a vertical strip of square frames.
+#[derive(Debug, Clone, Deserialize)]
+pub(in crate::engine) struct Animation {
+    /// Seconds every frame is shown for.
+    pub frame_time: f32,
+    /// Order the strip's frames are played in; all frames top to bottom when empty.
+    #[serde(default)]
+    pub frames: Vec<u32>,
+}
+
+/// Where the frames of one texture live in the array, as read by the shader.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub(in crate::engine) struct TextureAnimation {
+    /// First entry of this texture in the frame table.
+    pub first_frame: u32,
+    pub frame_count: u32,
+    pub frame_time: f32,
+    pub _padding: u32,
+}
+
+/// A texture array whose textures can be looked up by the name they were loaded from.
+/// Animated textures span several layers; the shader picks the current one.
+pub struct TextureArray {
+    pub(in crate::engine) texture: Texture,
+    pub(in crate::engine) indices: HashMap<String, u32>,
+    /// One [`TextureAnimation`] per texture index.
+    pub(in crate::engine) animations: wgpu::Buffer,
+    /// Array layer of every animation frame.
+    pub(in crate::engine) frames: wgpu::Buffer,
+}
+
+impl TextureArray {
+    /// The index vertices refer to the named texture by.
+    pub fn index(&self, name: &str) -> Option<u32> {
+        self.indices.get(name).copied()
+    }
+}
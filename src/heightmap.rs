@@ -0,0 +1,97 @@
+use crate::{
+    blocks::{BlockRegistry, BlockStateId},
+    chunk::CHUNK_SIZE,
+    coords::LocalPos,
+    engine::model::RenderClass,
+};
+
+/// Which blocks a heightmap tracks the highest of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeightmapKind {
+    /// Any block that is not air.
+    WorldSurface,
+    /// Blocks entities cannot move through.
+    MotionBlocking,
+    /// Blocks that stop light, such as stone but not glass or leaves.
+    LightBlocking,
+}
+
+impl HeightmapKind {
+    pub const ALL: [HeightmapKind; 3] = [
+        HeightmapKind::WorldSurface,
+        HeightmapKind::MotionBlocking,
+        HeightmapKind::LightBlocking,
+    ];
+
+    pub fn matches(&self, registry: &BlockRegistry, state: BlockStateId) -> bool {
+        if state.is_air() {
+            return false;
+        }
+
+        match self {
+            HeightmapKind::WorldSurface => true,
+            HeightmapKind::MotionBlocking => registry.block_of(state).solid,
+            HeightmapKind::LightBlocking => {
+                registry.block_of(state).render_class == RenderClass::Opaque
+            }
+        }
+    }
+}
+
+/// Highest matching block of every column of a chunk.
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    /// One above the local height of the highest match; 0 when the column has none.
+    values: [u8; CHUNK_SIZE * CHUNK_SIZE],
+}
+
+impl Default for Heightmap {
+    fn default() -> Self {
+        Self {
+            values: [0; CHUNK_SIZE * CHUNK_SIZE],
+        }
+    }
+}
+
+impl Heightmap {
+    fn index(x: usize, z: usize) -> usize {
+        z * CHUNK_SIZE + x
+    }
+
+    /// Builds a heightmap from scratch, given which blocks of the chunk match.
+    pub fn build(matches: impl Fn(LocalPos) -> bool) -> Self {
+        let mut heightmap = Self::default();
+        for z in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                if let Some(top) = LocalPos::column(x, z).rev().find(|&pos| matches(pos)) {
+                    heightmap.values[Self::index(x, z)] = (top.y + 1) as u8;
+                }
+            }
+        }
+        heightmap
+    }
+
+    /// Local height of the highest matching block in the column, if any.
+    pub fn get(&self, x: usize, z: usize) -> Option<usize> {
+        (self.values[Self::index(x, z)] as usize).checked_sub(1)
+    }
+
+    /// Updates the column after the block at `pos` changed. `matches` tells whether
+    /// the new block counts, `matches_at` whether the block at a lower height does.
+    pub fn update(&mut self, pos: LocalPos, matches: bool, matches_at: impl Fn(usize) -> bool) {
+        let LocalPos { x, y, z } = pos;
+        let value = &mut self.values[Self::index(x, z)];
+        let current = *value as usize;
+
+        if matches {
+            if y + 1 > current {
+                *value = (y + 1) as u8;
+            }
+        } else if y + 1 == current {
+            *value = (0..y)
+                .rev()
+                .find(|&below| matches_at(below))
+                .map_or(0, |below| (below + 1) as u8);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+};
+
+use crossbeam::channel::{Sender, unbounded};
+
+use crate::{
+    coords::ChunkPosition, region::RegionStorage, world::GenJob, world_meta::WorldMetadata,
+};
+
+/// Writes a file through a temporary sibling that is renamed over it, so a crash
+/// mid-write leaves either the old or the new contents, never a mix.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_owned();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut file = fs::File::create(&temp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)
+}
+
+enum IoRequest {
+    /// Read a chunk and queue its generation job, with the saved data if there is any.
+    Load(ChunkPosition),
+    /// Store an encoded chunk.
+    Save(ChunkPosition, Vec<u8>),
+    Metadata(WorldMetadata),
+    /// Signalled once everything requested before it is on disk.
+    Flush(Sender<()>),
+}
+
+/// Owns a world's files on a dedicated thread, so disk access never stalls a frame.
+/// Requests are handled in order, in batches of whatever queued up meanwhile;
+/// the regions touched by a batch are written once at its end.
+pub struct IoThread {
+    tx: Option<Sender<IoRequest>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl IoThread {
+    pub fn spawn(dir: PathBuf, job_tx: Sender<GenJob>) -> Self {
+        let (tx, rx) = unbounded::<IoRequest>();
+
+        let handle = thread::Builder::new()
+            .name("world io".to_owned())
+            .spawn(move || {
+                let mut regions = RegionStorage::new(dir.join("region"));
+
+                while let Ok(first_request) = rx.recv() {
+                    let mut requests = vec![first_request];
+                    requests.extend(rx.try_iter());
+
+                    let mut metadata = None;
+                    let mut flushed = Vec::new();
+                    for request in requests {
+                        match request {
+                            IoRequest::Load(position) => {
+                                let saved = regions.read_chunk(position).unwrap_or_else(|e| {
+                                    log::error!(
+                                        "Failed to read the region of chunk {:?}: {}",
+                                        position,
+                                        e
+                                    );
+                                    None
+                                });
+                                // The world stops listening when it shuts down.
+                                let _ = job_tx.send(GenJob { position, saved });
+                            }
+                            IoRequest::Save(position, data) => {
+                                if let Err(e) = regions.write_chunk(position, &data) {
+                                    log::error!("Failed to save chunk {:?}: {}", position, e);
+                                }
+                            }
+                            IoRequest::Metadata(latest) => metadata = Some(latest),
+                            IoRequest::Flush(done) => flushed.push(done),
+                        }
+                    }
+
+                    if let Err(e) = regions.flush() {
+                        log::error!("Failed to write region files: {}", e);
+                    }
+                    if let Some(metadata) = metadata
+                        && let Err(e) = metadata.write(&dir)
+                    {
+                        log::error!("Failed to save world metadata: {}", e);
+                    }
+                    for done in flushed {
+                        let _ = done.send(());
+                    }
+                }
+            })
+            .expect("Failed to spawn the world io thread");
+
+        Self {
+            tx: Some(tx),
+            handle: Some(handle),
+        }
+    }
+
+    fn send(&self, request: IoRequest) {
+        if let Some(tx) = &self.tx {
+            tx.send(request).expect("The world io thread stopped");
+        }
+    }
+
+    pub fn load(&self, position: ChunkPosition) {
+        self.send(IoRequest::Load(position));
+    }
+
+    /// Saves a chunk encoded with [`crate::chunk::Chunk::encode`].
+    pub fn save(&self, position: ChunkPosition, data: Vec<u8>) {
+        self.send(IoRequest::Save(position, data));
+    }
+
+    pub fn save_metadata(&self, metadata: WorldMetadata) {
+        self.send(IoRequest::Metadata(metadata));
+    }
+
+    /// Blocks until every earlier request has been written to disk.
+    pub fn flush(&self) {
+        let (done_tx, done_rx) = unbounded();
+        self.send(IoRequest::Flush(done_tx));
+        let _ = done_rx.recv();
+    }
+}
+
+impl Drop for IoThread {
+    fn drop(&mut self) {
+        // Closing the channel ends the thread once the queued requests are handled.
+        self.tx.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -1,14 +1,20 @@
 mod blocks;
 mod chunk;
+mod coords;
 mod engine;
+mod heightmap;
+mod io_thread;
+mod palette;
+mod region;
 mod world;
 mod world_gen;
+mod world_meta;
 
-use std::time::Instant;
+use std::{path::Path, sync::Arc, time::Instant};
 
 use anyhow::Ok;
-use blocks::{Block, BlockFace};
-use cgmath::{Deg, InnerSpace, Vector3};
+use blocks::BlockRegistry;
+use cgmath::{InnerSpace, Rad, Vector3};
 use engine::{
     app::App,
     camera::Camera,
@@ -16,29 +22,33 @@ use engine::{
 };
 use winit::keyboard::KeyCode;
 use world::World;
+use world_gen::GeneratorSettings;
 
 fn main() -> anyhow::Result<()> {
+    let registry = Arc::new(BlockRegistry::load("blocks.ron")?);
+
+    let path = Path::new("world");
+    let world = if World::exists(path) {
+        World::open(path, registry)?
+    } else {
+        World::create(path, rand::random(), GeneratorSettings::default(), registry)?
+    };
+    let camera = world.metadata().camera;
+
+    // The camera goes first, so the world loads its first chunks around the
+    // saved camera rather than the renderer's default one.
     App::default()
-        .add_object(World::new(12))
-        .add_object(Camera::new((5.0, 100.0, 25.0), Deg(-90.0), Deg(-20.0)))
+        .add_object(Camera::new(
+            camera.position,
+            Rad(camera.yaw),
+            Rad(camera.pitch),
+        ))
+        .add_object(world)
         .add_object(FPSCounter::default())
         .run()?;
     Ok(())
 }
 
-#[derive(Default, Debug, Clone, Copy)]
-struct GrassBlock {}
-
-impl Block for GrassBlock {
-    fn get_texture_index(&self, face: blocks::BlockFace) -> u32 {
-        match face {
-            BlockFace::Top => 0,
-            BlockFace::Bottom => 1,
-            _ => 2,
-        }
-    }
-}
-
 impl Object for Camera {
     fn update(&mut self, ctx: &mut Context, delta: f32) {
         let speed = 5.0;
@@ -0,0 +1,203 @@
+use crate::blocks::BlockStateId;
+
+/// Fixed-size storage of block states as indices into a palette of the states
+/// actually present, bit-packed into `u64` words. A container holding one state
+/// needs no index data at all; the index width grows as new states appear.
+#[derive(Debug, Clone)]
+pub struct PalettedContainer {
+    palette: Vec<BlockStateId>,
+    bits: u32,
+    data: Vec<u64>,
+    len: usize,
+}
+
+impl PalettedContainer {
+    /// A container of `len` entries, all set to `state`.
+    pub fn new(len: usize, state: BlockStateId) -> Self {
+        Self {
+            palette: vec![state],
+            bits: 0,
+            data: Vec::new(),
+            len,
+        }
+    }
+
+    /// Rebuilds a container from the parts returned by [`PalettedContainer::palette`],
+    /// [`PalettedContainer::bits`] and [`PalettedContainer::data`]. Returns `None`
+    /// if they do not describe `len` valid entries.
+    pub fn from_parts(
+        len: usize,
+        palette: Vec<BlockStateId>,
+        bits: u32,
+        data: Vec<u64>,
+    ) -> Option<Self> {
+        if palette.is_empty() || bits > 16 || palette.len() > 1 << bits {
+            return None;
+        }
+        let words = if bits == 0 {
+            0
+        } else {
+            len.div_ceil(Self::entries_per_word(bits))
+        };
+        if data.len() != words {
+            return None;
+        }
+
+        let container = Self {
+            palette,
+            bits,
+            data,
+            len,
+        };
+        (0..len)
+            .all(|index| container.index_at(index) < container.palette.len())
+            .then_some(container)
+    }
+
+    pub fn palette(&self) -> &[BlockStateId] {
+        &self.palette
+    }
+
+    /// Width of each packed palette index, in bits.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// The packed palette indices, each word holding as many whole indices as fit.
+    pub fn data(&self) -> &[u64] {
+        &self.data
+    }
+
+    pub fn get(&self, index: usize) -> BlockStateId {
+        self.palette[self.index_at(index)]
+    }
+
+    /// Stores `state` at `index`, returning the state it replaced.
+    pub fn set(&mut self, index: usize, state: BlockStateId) -> BlockStateId {
+        let palette_index = match self.palette.iter().position(|&entry| entry == state) {
+            Some(palette_index) => palette_index,
+            None => {
+                if self.palette.len() >= 1 << self.bits {
+                    self.resize(self.bits + 1);
+                }
+                self.palette.push(state);
+                self.palette.len() - 1
+            }
+        };
+
+        let old = self.get(index);
+        self.set_index_at(index, palette_index);
+        old
+    }
+
+    fn entries_per_word(bits: u32) -> usize {
+        (u64::BITS / bits) as usize
+    }
+
+    fn index_at(&self, index: usize) -> usize {
+        if self.bits == 0 {
+            return 0;
+        }
+
+        let per_word = Self::entries_per_word(self.bits);
+        let word = self.data[index / per_word];
+        let shift = (index % per_word) as u32 * self.bits;
+
+        ((word >> shift) & ((1 << self.bits) - 1)) as usize
+    }
+
+    fn set_index_at(&mut self, index: usize, palette_index: usize) {
+        if self.bits == 0 {
+            return;
+        }
+
+        let per_word = Self::entries_per_word(self.bits);
+        let mask = (1u64 << self.bits) - 1;
+        let shift = (index % per_word) as u32 * self.bits;
+        let word = &mut self.data[index / per_word];
+
+        *word = (*word & !(mask << shift)) | ((palette_index as u64) << shift);
+    }
+
+    /// Repacks every index with a new width.
+    fn resize(&mut self, bits: u32) {
+        let indices: Vec<usize> = (0..self.len).map(|index| self.index_at(index)).collect();
+
+        self.bits = bits;
+        self.data = vec![0; self.len.div_ceil(Self::entries_per_word(bits))];
+        for (index, palette_index) in indices.into_iter().enumerate() {
+            self.set_index_at(index, palette_index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEN: usize = 4096;
+
+    #[test]
+    fn index_width_grows_with_the_palette() {
+        let mut container = PalettedContainer::new(LEN, BlockStateId::AIR);
+        assert_eq!(container.bits(), 0);
+
+        // Spread the writes so indices land at every offset inside their words.
+        let index_of = |state: usize| state * 13 % LEN;
+        for state in 1..=300 {
+            container.set(index_of(state), BlockStateId(state as u16));
+
+            let palette_len = state + 1;
+            let expected_bits = usize::BITS - (palette_len - 1).leading_zeros();
+            assert_eq!(container.bits(), expected_bits, "{} states", palette_len);
+            for earlier in 1..=state {
+                assert_eq!(
+                    container.get(index_of(earlier)),
+                    BlockStateId(earlier as u16)
+                );
+            }
+        }
+        assert_eq!(container.bits(), 9);
+        assert_eq!(container.get(1), BlockStateId::AIR);
+    }
+
+    #[test]
+    fn writes_around_word_boundaries_stay_apart() {
+        for bits in 1..=9u32 {
+            let mut container = PalettedContainer::new(LEN, BlockStateId::AIR);
+            for state in 1..1u16 << bits {
+                container.set(LEN - 1, BlockStateId(state));
+            }
+            assert_eq!(container.bits(), bits);
+
+            let per_word = 64 / bits as usize;
+            let boundary = [per_word - 1, per_word, 2 * per_word - 1, 2 * per_word];
+            let top = BlockStateId((1 << bits) - 1);
+            for (offset, &index) in boundary.iter().enumerate() {
+                let state = if offset % 2 == 0 {
+                    top
+                } else {
+                    BlockStateId(1)
+                };
+                assert_eq!(container.set(index, state), BlockStateId::AIR);
+            }
+            for (offset, &index) in boundary.iter().enumerate() {
+                let state = if offset % 2 == 0 {
+                    top
+                } else {
+                    BlockStateId(1)
+                };
+                assert_eq!(
+                    container.get(index),
+                    state,
+                    "{} bits, index {}",
+                    bits,
+                    index
+                );
+            }
+            assert_eq!(container.get(per_word - 2), BlockStateId::AIR);
+            assert_eq!(container.get(2 * per_word + 1), BlockStateId::AIR);
+            assert_eq!(container.get(LEN - 1), top);
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
+
+use crate::{coords::ChunkPosition, io_thread::write_atomic};
+
+/// Chunks per region along x and along z. A region holds a single horizontal
+/// layer of chunks, so its y matches the chunk y.
+pub const REGION_SIZE: i32 = 32;
+const REGION_CHUNKS: usize = (REGION_SIZE * REGION_SIZE) as usize;
+
+const MAGIC: [u8; 4] = *b"MCRG";
+const REGION_FORMAT_VERSION: u16 = 1;
+/// Magic, version and a reserved `u16`.
+const HEADER_LEN: usize = 8;
+/// Offset and length, both `u32`, for every chunk of the region.
+const TABLE_LEN: usize = REGION_CHUNKS * 8;
+/// Clean regions kept in memory after a flush; the least recently used ones
+/// beyond it are dropped and read again when next needed.
+const MAX_CACHED_REGIONS: usize = 64;
+/// Bound on a decompressed chunk, well above the largest encoding, so corrupt
+/// data cannot exhaust memory.
+const MAX_CHUNK_LEN: u64 = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegionPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl RegionPos {
+    /// The region containing a chunk.
+    pub fn of(chunk: ChunkPosition) -> Self {
+        Self {
+            x: chunk.x.div_euclid(REGION_SIZE),
+            y: chunk.y,
+            z: chunk.z.div_euclid(REGION_SIZE),
+        }
+    }
+
+    /// Index of a chunk of this region in its offset table.
+    fn slot(chunk: ChunkPosition) -> usize {
+        (chunk.z.rem_euclid(REGION_SIZE) * REGION_SIZE + chunk.x.rem_euclid(REGION_SIZE)) as usize
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("r.{}.{}.{}.region", self.x, self.y, self.z)
+    }
+}
+
+/// The chunks of one region file, each kept zlib compressed.
+///
+/// The file starts with the magic `MCRG`, a `u16` format version and a reserved
+/// `u16`, followed by an offset table of 1024 little endian `(offset, length)`
+/// `u32` pairs in z, then x order. Offsets are from the start of the file; a
+/// zero length marks a chunk that was never saved. The compressed chunks follow.
+#[derive(Debug, Clone)]
+pub struct Region {
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+impl Default for Region {
+    fn default() -> Self {
+        Self {
+            chunks: vec![None; REGION_CHUNKS],
+        }
+    }
+}
+
+impl Region {
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let bytes = fs::read(path)?;
+        if bytes.len() < HEADER_LEN + TABLE_LEN || bytes[..4] != MAGIC {
+            return Err(anyhow!("{} is not a region file", path.display()));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != REGION_FORMAT_VERSION {
+            return Err(anyhow!(
+                "Unsupported region format version {} in {}",
+                version,
+                path.display()
+            ));
+        }
+
+        let field = |at: usize| {
+            u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]) as usize
+        };
+        let chunks = (0..REGION_CHUNKS)
+            .map(|slot| {
+                let entry = HEADER_LEN + slot * 8;
+                let (offset, len) = (field(entry), field(entry + 4));
+                if len == 0 {
+                    return Ok(None);
+                }
+
+                bytes
+                    .get(offset..offset + len)
+                    .map(|chunk| Some(chunk.to_vec()))
+                    .ok_or_else(|| anyhow!("Chunk {} is out of bounds in {}", slot, path.display()))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self { chunks })
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + TABLE_LEN);
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&REGION_FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+
+        let mut offset = HEADER_LEN + TABLE_LEN;
+        for chunk in &self.chunks {
+            let len = chunk.as_ref().map_or(0, Vec::len);
+            let entry_offset = if len == 0 { 0 } else { offset };
+            bytes.extend_from_slice(&(entry_offset as u32).to_le_bytes());
+            bytes.extend_from_slice(&(len as u32).to_le_bytes());
+            offset += len;
+        }
+        for chunk in self.chunks.iter().flatten() {
+            bytes.extend_from_slice(chunk);
+        }
+
+        write_atomic(path, &bytes)?;
+        Ok(())
+    }
+
+    /// The compressed data of a chunk, if it was saved.
+    pub fn get(&self, chunk: ChunkPosition) -> Option<&[u8]> {
+        self.chunks[RegionPos::slot(chunk)].as_deref()
+    }
+
+    pub fn set(&mut self, chunk: ChunkPosition, data: Vec<u8>) {
+        self.chunks[RegionPos::slot(chunk)] = Some(data);
+    }
+}
+
+pub fn compress(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(bytes)
+        .and_then(|_| encoder.finish())
+        .expect("Writing to a Vec cannot fail")
+}
+
+pub fn decompress(bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    ZlibDecoder::new(bytes)
+        .take(MAX_CHUNK_LEN + 1)
+        .read_to_end(&mut decompressed)?;
+    if decompressed.len() as u64 > MAX_CHUNK_LEN {
+        return Err(anyhow!("Chunk data is larger than {} bytes", MAX_CHUNK_LEN));
+    }
+
+    Ok(decompressed)
+}
+
+/// The region files of a directory. Regions are read when first needed and
+/// cached, up to [`MAX_CACHED_REGIONS`] of them once flushed; saved chunks are
+/// written to disk on [`RegionStorage::flush`].
+pub struct RegionStorage {
+    dir: PathBuf,
+    /// Cached regions, with when each was last used.
+    regions: HashMap<RegionPos, (Region, u64)>,
+    dirty: HashSet<RegionPos>,
+    /// Counts region uses, to order them by recency.
+    uses: u64,
+}
+
+impl RegionStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            regions: HashMap::new(),
+            dirty: HashSet::new(),
+            uses: 0,
+        }
+    }
+
+    fn region(&mut self, pos: RegionPos) -> anyhow::Result<&mut Region> {
+        if !self.regions.contains_key(&pos) {
+            let path = self.dir.join(pos.file_name());
+            let region = if path.is_file() {
+                match Region::read(&path) {
+                    Ok(region) => region,
+                    // The file could not be read at all; it may work next time.
+                    Err(e) if e.is::<io::Error>() => return Err(e),
+                    Err(e) => {
+                        // Keep the broken file for inspection, out of the way of new saves.
+                        let corrupt_path = path.with_extension("region.corrupt");
+                        log::error!(
+                            "{}; moving it to {} and starting the region over",
+                            e,
+                            corrupt_path.display()
+                        );
+                        fs::rename(&path, &corrupt_path)?;
+                        Region::default()
+                    }
+                }
+            } else {
+                Region::default()
+            };
+            self.regions.insert(pos, (region, 0));
+        }
+
+        self.uses += 1;
+        let (region, last_used) = self.regions.get_mut(&pos).unwrap();
+        *last_used = self.uses;
+        Ok(region)
+    }
+
+    /// The compressed data of a saved chunk, or `None` if it was never saved.
+    pub fn read_chunk(&mut self, position: ChunkPosition) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self
+            .region(RegionPos::of(position))?
+            .get(position)
+            .map(<[u8]>::to_vec))
+    }
+
+    /// Stores a chunk encoded with [`crate::chunk::Chunk::encode`].
+    pub fn write_chunk(&mut self, position: ChunkPosition, data: &[u8]) -> anyhow::Result<()> {
+        let pos = RegionPos::of(position);
+        self.region(pos)?.set(position, compress(data));
+        self.dirty.insert(pos);
+        Ok(())
+    }
+
+    /// Writes every region with chunks saved since the last flush, then drops
+    /// the least recently used regions beyond [`MAX_CACHED_REGIONS`].
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        if !self.dirty.is_empty() {
+            fs::create_dir_all(&self.dir)?;
+
+            let dirty: Vec<RegionPos> = self.dirty.iter().copied().collect();
+            for pos in dirty {
+                if let Some((region, _)) = self.regions.get(&pos) {
+                    region.write(&self.dir.join(pos.file_name()))?;
+                }
+                self.dirty.remove(&pos);
+            }
+        }
+
+        if self.regions.len() > MAX_CACHED_REGIONS {
+            let mut by_use: Vec<(RegionPos, u64)> = self
+                .regions
+                .iter()
+                .map(|(&pos, &(_, last_used))| (pos, last_used))
+                .collect();
+            by_use.sort_by_key(|&(_, last_used)| last_used);
+            let excess = self.regions.len() - MAX_CACHED_REGIONS;
+            for (pos, _) in by_use.into_iter().take(excess) {
+                self.regions.remove(&pos);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory of its own for each test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mineclone-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn regions_round_trip_through_files() {
+        let dir = temp_dir("region-round-trip");
+        let path = dir.join("region");
+        let first = ChunkPosition::new(0, 0, 0);
+        let last = ChunkPosition::new(REGION_SIZE - 1, 0, REGION_SIZE - 1);
+
+        let mut region = Region::default();
+        region.set(first, vec![1, 2, 3]);
+        region.set(last, vec![4; 100]);
+        region.write(&path).unwrap();
+        let read = Region::read(&path).unwrap();
+
+        assert_eq!(read.get(first), Some(&[1, 2, 3][..]));
+        assert_eq!(read.get(last), Some(&[4; 100][..]));
+        assert_eq!(read.get(ChunkPosition::new(1, 0, 0)), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn chunks_round_trip_through_storage() {
+        let dir = temp_dir("region-storage");
+        let position = ChunkPosition::new(-40, 3, 70);
+
+        let mut storage = RegionStorage::new(&dir);
+        storage.write_chunk(position, b"chunk data").unwrap();
+        storage.flush().unwrap();
+
+        let mut reopened = RegionStorage::new(&dir);
+        let data = reopened.read_chunk(position).unwrap().unwrap();
+        assert_eq!(decompress(&data).unwrap(), b"chunk data");
+        assert_eq!(
+            reopened.read_chunk(ChunkPosition::new(0, 0, 0)).unwrap(),
+            None
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn truncated_files_are_moved_aside() {
+        let dir = temp_dir("region-truncated");
+        let position = ChunkPosition::new(0, 0, 0);
+        let file_name = RegionPos::of(position).file_name();
+
+        let mut region = Region::default();
+        region.set(position, compress(b"old"));
+        region.write(&dir.join(&file_name)).unwrap();
+        let bytes = fs::read(dir.join(&file_name)).unwrap();
+        fs::write(dir.join(&file_name), &bytes[..HEADER_LEN + 10]).unwrap();
+
+        let mut storage = RegionStorage::new(&dir);
+        assert_eq!(storage.read_chunk(position).unwrap(), None);
+        assert!(dir.join(format!("{}.corrupt", file_name)).is_file());
+
+        storage.write_chunk(position, b"new").unwrap();
+        storage.flush().unwrap();
+        let data = RegionStorage::new(&dir)
+            .read_chunk(position)
+            .unwrap()
+            .unwrap();
+        assert_eq!(decompress(&data).unwrap(), b"new");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn least_recently_used_regions_are_evicted() {
+        let dir = temp_dir("region-eviction");
+        let mut storage = RegionStorage::new(&dir);
+        // One chunk in each of more regions than are cached.
+        let positions: Vec<ChunkPosition> = (0..MAX_CACHED_REGIONS as i32 + 2)
+            .map(|y| ChunkPosition::new(0, y, 0))
+            .collect();
+        for &position in &positions {
+            storage.write_chunk(position, &[position.y as u8]).unwrap();
+        }
+        // Using the first region again keeps it over the second.
+        storage.read_chunk(positions[0]).unwrap();
+        storage.flush().unwrap();
+
+        assert_eq!(storage.regions.len(), MAX_CACHED_REGIONS);
+        assert!(storage.regions.contains_key(&RegionPos::of(positions[0])));
+        assert!(!storage.regions.contains_key(&RegionPos::of(positions[1])));
+        assert!(!storage.regions.contains_key(&RegionPos::of(positions[2])));
+
+        // Evicted regions are read back from disk.
+        let data = storage.read_chunk(positions[1]).unwrap().unwrap();
+        assert_eq!(decompress(&data).unwrap(), [1]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,48 +1,209 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
+use anyhow::anyhow;
 use cgmath::Point3;
 use crossbeam::channel::{Receiver, Sender};
+use rand::Rng;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::{
-    chunk::{Chunk, ChunkPosition},
+    blocks::{BlockFace, BlockRegistry, BlockStateId, BlockTextures, ItemStack, Tool},
+    chunk::{CHUNK_SIZE, Chunk, ChunkSnapshot, MAX_LOD, MeshData},
+    coords::{BlockPos, ChunkPosition, LocalPos},
     engine::object::{Context, Object},
-    world_gen::WorldGenerator,
+    heightmap::HeightmapKind,
+    io_thread::IoThread,
+    region,
+    world_gen::{GeneratorSettings, WorldGenerator},
+    world_meta::{CameraState, METADATA_FILE, WORLD_FORMAT_VERSION, WorldMetadata},
 };
 
-struct GenJob {
+/// World ticks per second; block behaviours run on ticks.
+const TICKS_PER_SECOND: f32 = 20.0;
+/// Blocks picked for a random tick in every chunk, per tick.
+const RANDOM_TICK_SPEED: usize = 3;
+/// Upper bound of neighbour updates handled in one tick, so update loops cannot stall a frame.
+const MAX_NEIGHBOR_UPDATES: usize = 4096;
+/// Horizontal chunk radius loaded around the camera unless changed.
+const DEFAULT_RENDER_DISTANCE: u32 = 12;
+/// Chunks loaded above and below the camera unless changed.
+const DEFAULT_VERTICAL_RENDER_DISTANCE: u32 = 4;
+/// Horizontal chunk distances beyond which levels of detail start unless changed.
+const DEFAULT_LOD_DISTANCES: [u32; MAX_LOD as usize] = [6, 10, 16];
+/// How far above the spawn block a new world's camera starts.
+const SPAWN_EYE_HEIGHT: f32 = 1.6;
+/// Seconds between saves of every modified chunk and the metadata.
+const AUTOSAVE_INTERVAL: f32 = 30.0;
+/// Chunk meshes uploaded to the GPU per frame unless changed.
+const DEFAULT_MESH_UPLOAD_BUDGET: usize = 8;
+
+/// Returned when a block is read or written in a chunk that is not loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkNotLoaded(pub ChunkPosition);
+
+impl fmt::Display for ChunkNotLoaded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Chunk ({}, {}, {}) is not loaded",
+            self.0.x, self.0.y, self.0.z
+        )
+    }
+}
+
+impl std::error::Error for ChunkNotLoaded {}
+
+pub struct GenJob {
+    pub position: ChunkPosition,
+    /// Compressed data of the chunk if it was saved; it is generated otherwise.
+    pub saved: Option<Vec<u8>>,
+}
+
+/// A chunk mesh built on the worker pool, waiting to be uploaded.
+struct MeshResult {
     position: ChunkPosition,
+    /// The [`Chunk::mesh_generation`] the mesh was built for.
+    generation: u64,
+    meshes: [MeshData; 3],
 }
 
 pub struct World {
+    metadata: WorldMetadata,
+    registry: Arc<BlockRegistry>,
+    textures: Option<BlockTextures>,
     chunks: HashMap<ChunkPosition, Chunk>,
+    /// Heights of the loaded chunks of every chunk column, for surface lookups.
+    columns: HashMap<(i32, i32), BTreeSet<i32>>,
     in_flight: HashSet<ChunkPosition>,
-    job_tx: Sender<GenJob>,
     result_rx: Receiver<Chunk>,
+    /// Reads saved chunks ahead of generation and writes modified ones back.
+    io: IoThread,
+    autosave_timer: f32,
+    mesh_tx: Sender<MeshResult>,
+    mesh_rx: Receiver<MeshResult>,
+    next_mesh_generation: u64,
+    /// Finished chunk meshes uploaded per frame, spreading the cost of large
+    /// batches over several frames.
+    pub mesh_upload_budget: usize,
 
     pub render_distance: u32,
+    /// Chunks loaded above and below the camera's chunk.
+    pub vertical_render_distance: u32,
+    /// Horizontal chunk distances from the camera beyond which chunks are meshed
+    /// at levels of detail 1 to [`MAX_LOD`], in increasing order.
+    pub lod_distances: [u32; MAX_LOD as usize],
     last_player_chunk: Option<ChunkPosition>,
+
+    tick: u64,
+    tick_accumulator: f32,
+    scheduled_ticks: BTreeMap<u64, Vec<BlockPos>>,
+    /// Pending `(position, changed neighbour)` notifications.
+    neighbor_updates: VecDeque<(BlockPos, BlockPos)>,
 }
 
 impl World {
-    pub fn new(render_distance: u32) -> Self {
+    /// Whether `dir` holds a world that [`World::open`] can try to open.
+    pub fn exists(dir: impl AsRef<Path>) -> bool {
+        dir.as_ref().join(METADATA_FILE).is_file()
+    }
+
+    /// Opens the world saved in `dir`.
+    pub fn open(dir: impl Into<PathBuf>, registry: Arc<BlockRegistry>) -> anyhow::Result<Self> {
+        let dir = dir.into();
+        let metadata = WorldMetadata::read(&dir)?;
+        let generator = WorldGenerator::new(metadata.seed, metadata.generator.clone(), &registry)?;
+
+        Ok(Self::with_metadata(dir, metadata, generator, registry))
+    }
+
+    /// Creates a new world in `dir`, which must not hold a world already.
+    pub fn create(
+        dir: impl Into<PathBuf>,
+        seed: u32,
+        settings: GeneratorSettings,
+        registry: Arc<BlockRegistry>,
+    ) -> anyhow::Result<Self> {
+        let dir = dir.into();
+        if Self::exists(&dir) {
+            return Err(anyhow!("{} already holds a world", dir.display()));
+        }
+
+        let generator = WorldGenerator::new(seed, settings.clone(), &registry)?;
+        let spawn = BlockPos::new(0, generator.height_at(0, 0).max(settings.sea_level), 0);
+        let metadata = WorldMetadata {
+            format_version: WORLD_FORMAT_VERSION,
+            seed,
+            generator: settings,
+            spawn,
+            camera: CameraState {
+                position: [
+                    spawn.x as f32 + 0.5,
+                    spawn.y as f32 + SPAWN_EYE_HEIGHT,
+                    spawn.z as f32 + 0.5,
+                ],
+                yaw: -std::f32::consts::FRAC_PI_2,
+                pitch: 0.0,
+            },
+            time: 0,
+        };
+        metadata.write(&dir)?;
+
+        Ok(Self::with_metadata(dir, metadata, generator, registry))
+    }
+
+    fn with_metadata(
+        dir: PathBuf,
+        metadata: WorldMetadata,
+        generator: WorldGenerator,
+        registry: Arc<BlockRegistry>,
+    ) -> Self {
         let (job_tx, job_rx) = crossbeam::channel::unbounded::<GenJob>();
         let (result_tx, result_rx) = crossbeam::channel::unbounded::<Chunk>();
-        let generator = Arc::new(WorldGenerator::new(rand::random()));
+        let (mesh_tx, mesh_rx) = crossbeam::channel::unbounded::<MeshResult>();
+        let generator = Arc::new(generator);
 
         rayon::spawn({
             let generator = Arc::clone(&generator);
+            let registry = Arc::clone(&registry);
             move || {
                 while let Ok(first_job) = job_rx.recv() {
                     let mut jobs = vec![first_job];
                     jobs.extend(job_rx.try_iter());
 
                     jobs.into_par_iter().for_each(|job| {
-                        let mut chunk = Chunk::new(job.position);
-                        generator.generate_chunk(&mut chunk);
+                        let saved = job.saved.and_then(|data| {
+                            region::decompress(&data)
+                                .and_then(|bytes| Ok(Chunk::decode(&bytes, Arc::clone(&registry))?))
+                                .and_then(|chunk| {
+                                    if chunk.position != job.position {
+                                        return Err(anyhow!(
+                                            "Its region slot holds chunk {:?}",
+                                            chunk.position
+                                        ));
+                                    }
+                                    Ok(chunk)
+                                })
+                                .inspect_err(|e| {
+                                    log::error!(
+                                        "Failed to load chunk {:?}, regenerating it: {}",
+                                        job.position,
+                                        e
+                                    )
+                                })
+                                .ok()
+                        });
+
+                        let chunk = saved.unwrap_or_else(|| {
+                            let mut chunk = Chunk::new(job.position, Arc::clone(&registry));
+                            generator.generate_chunk(&mut chunk);
+                            chunk.modified = false;
+                            chunk
+                        });
 
                         result_tx.send(chunk).unwrap();
                     });
@@ -51,146 +212,556 @@ impl World {
         });
 
         Self {
+            io: IoThread::spawn(dir, job_tx),
+            autosave_timer: 0.0,
+            mesh_tx,
+            mesh_rx,
+            next_mesh_generation: 0,
+            mesh_upload_budget: DEFAULT_MESH_UPLOAD_BUDGET,
+            tick: metadata.time,
+            metadata,
+            registry,
+            textures: None,
             chunks: HashMap::new(),
+            columns: HashMap::new(),
             in_flight: HashSet::new(),
-            job_tx,
             result_rx,
-            render_distance,
+            render_distance: DEFAULT_RENDER_DISTANCE,
+            vertical_render_distance: DEFAULT_VERTICAL_RENDER_DISTANCE,
+            lod_distances: DEFAULT_LOD_DISTANCES,
             last_player_chunk: None,
+            tick_accumulator: 0.0,
+            scheduled_ticks: BTreeMap::new(),
+            neighbor_updates: VecDeque::new(),
         }
     }
 
+    pub fn metadata(&self) -> &WorldMetadata {
+        &self.metadata
+    }
+
+    pub fn registry(&self) -> &BlockRegistry {
+        &self.registry
+    }
+
+    /// The shared block texture array, available once the world has started.
+    pub fn block_textures(&self) -> &BlockTextures {
+        self.textures
+            .as_ref()
+            .expect("Block textures are loaded when the world starts")
+    }
+
     pub fn get_chunk(&self, position: &ChunkPosition) -> Option<&Chunk> {
         self.chunks.get(position)
     }
 
+    #[allow(dead_code)]
     pub fn get_chunk_mut(&mut self, position: &ChunkPosition) -> Option<&mut Chunk> {
         self.chunks.get_mut(position)
     }
 
+    /// The block at a world position.
+    pub fn get_block(&self, pos: BlockPos) -> Result<BlockStateId, ChunkNotLoaded> {
+        let chunk = pos.chunk();
+        self.chunks
+            .get(&chunk)
+            .map(|loaded| loaded.get_block(pos.local()))
+            .ok_or(ChunkNotLoaded(chunk))
+    }
+
+    /// World height of the highest block of the given kind in the column at `x`, `z`,
+    /// among the loaded chunks.
+    #[allow(dead_code)]
+    pub fn height(&self, kind: HeightmapKind, x: i32, z: i32) -> Option<i32> {
+        let pos = BlockPos::new(x, 0, z);
+        let (column, local) = (pos.chunk(), pos.local());
+
+        self.columns
+            .get(&column.column())?
+            .iter()
+            .rev()
+            .find_map(|&chunk_y| {
+                let chunk = self.chunks.get(&ChunkPosition {
+                    y: chunk_y,
+                    ..column
+                })?;
+                let local_y = chunk.height(kind, local.x, local.z)?;
+                Some(chunk.position.origin().y + local_y as i32)
+            })
+    }
+
+    /// Writes a block without running any behaviour. Chunks touching the edited
+    /// block are remeshed too, since it may hide, reveal or shade their faces.
+    pub fn set_block(&mut self, pos: BlockPos, state: BlockStateId) -> Result<(), ChunkNotLoaded> {
+        let position = pos.chunk();
+        let chunk = self
+            .chunks
+            .get_mut(&position)
+            .ok_or(ChunkNotLoaded(position))?;
+        if !chunk.set_block(pos.local(), state) {
+            return Ok(());
+        }
+
+        for neighbor in position.surrounding() {
+            let origin = neighbor.origin();
+            let size = CHUNK_SIZE as i32;
+            let touches = |block: i32, start: i32| (start - 1..=start + size).contains(&block);
+            if touches(pos.x, origin.x)
+                && touches(pos.y, origin.y)
+                && touches(pos.z, origin.z)
+                && let Some(neighbor_chunk) = self.chunks.get_mut(&neighbor)
+            {
+                neighbor_chunk.need_rebuilt = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a block and notifies its neighbours, without placing or breaking callbacks.
+    pub fn replace_block(
+        &mut self,
+        pos: BlockPos,
+        state: BlockStateId,
+    ) -> Result<(), ChunkNotLoaded> {
+        self.set_block(pos, state)?;
+
+        for face in BlockFace::ALL {
+            self.neighbor_updates.push_back((pos.neighbor(face), pos));
+        }
+        Ok(())
+    }
+
+    /// Places a block, running its `on_placed` behaviour.
+    pub fn place_block(
+        &mut self,
+        pos: BlockPos,
+        state: BlockStateId,
+    ) -> Result<(), ChunkNotLoaded> {
+        self.replace_block(pos, state)?;
+
+        self.registry
+            .block_of(state)
+            .behavior
+            .get()
+            .on_placed(self, pos, state);
+        Ok(())
+    }
+
+    /// Breaks a block into air with `tool`, running its `on_broken` behaviour first.
+    /// Returns the rolled drops, or `None` with the block left in place if it
+    /// cannot be broken.
+    pub fn break_block(
+        &mut self,
+        pos: BlockPos,
+        tool: &Tool,
+    ) -> Result<Option<Vec<ItemStack>>, ChunkNotLoaded> {
+        let state = self.get_block(pos)?;
+        let registry = Arc::clone(&self.registry);
+        let Some(drops) = registry.mine(state, tool, &mut rand::rng()) else {
+            return Ok(None);
+        };
+
+        registry
+            .block_of(state)
+            .behavior
+            .get()
+            .on_broken(self, pos, state);
+        self.replace_block(pos, BlockStateId::AIR)?;
+
+        Ok(Some(drops))
+    }
+
+    /// Blows up the blocks around `center`. The power of the explosion falls off
+    /// by one per block of distance, and blocks that do not resist what reaches
+    /// them break as if mined by hand. Returns their drops.
+    #[allow(dead_code)]
+    pub fn explode(&mut self, center: BlockPos, power: f32) -> Vec<ItemStack> {
+        let radius = power.ceil() as i32;
+        let mut drops = Vec::new();
+        for dx in -radius..=radius {
+            for dy in -radius..=radius {
+                for dz in -radius..=radius {
+                    let pos = center.offset(dx, dy, dz);
+                    let distance = ((dx * dx + dy * dy + dz * dz) as f32).sqrt();
+                    let Ok(state) = self.get_block(pos) else {
+                        continue;
+                    };
+                    if state.is_air() || self.registry.resists_explosion(state, power - distance) {
+                        continue;
+                    }
+
+                    if let Ok(Some(mut block_drops)) = self.break_block(pos, &Tool::HAND) {
+                        drops.append(&mut block_drops);
+                    }
+                }
+            }
+        }
+        drops
+    }
+
+    /// Uses the block at `pos`. Returns whether its behaviour handled the use.
+    #[allow(dead_code)]
+    pub fn use_block(&mut self, pos: BlockPos) -> Result<bool, ChunkNotLoaded> {
+        let state = self.get_block(pos)?;
+
+        Ok(self
+            .registry
+            .block_of(state)
+            .behavior
+            .get()
+            .on_use(self, pos, state))
+    }
+
+    /// Runs the `on_scheduled_tick` behaviour of the block at `pos` after `delay` ticks.
+    pub fn schedule_tick(&mut self, pos: BlockPos, delay: u64) {
+        self.scheduled_ticks
+            .entry(self.tick + delay.max(1))
+            .or_default()
+            .push(pos);
+    }
+
+    fn run_tick(&mut self) {
+        self.tick += 1;
+
+        let registry = Arc::clone(&self.registry);
+        while let Some(entry) = self.scheduled_ticks.first_entry() {
+            if *entry.key() > self.tick {
+                break;
+            }
+
+            for pos in entry.remove() {
+                if let Ok(state) = self.get_block(pos) {
+                    registry
+                        .block_of(state)
+                        .behavior
+                        .get()
+                        .on_scheduled_tick(self, pos, state);
+                }
+            }
+        }
+
+        let mut rng = rand::rng();
+        let mut random_ticks = Vec::new();
+        for chunk in self.chunks.values().filter(|chunk| !chunk.is_empty()) {
+            for _ in 0..RANDOM_TICK_SPEED {
+                let pos = LocalPos::new(
+                    rng.random_range(0..CHUNK_SIZE),
+                    rng.random_range(0..CHUNK_SIZE),
+                    rng.random_range(0..CHUNK_SIZE),
+                );
+                if !chunk.get_block(pos).is_air() {
+                    random_ticks.push(chunk.position.block_pos(pos));
+                }
+            }
+        }
+        for pos in random_ticks {
+            if let Ok(state) = self.get_block(pos) {
+                registry
+                    .block_of(state)
+                    .behavior
+                    .get()
+                    .on_random_tick(self, pos, state);
+            }
+        }
+
+        for _ in 0..MAX_NEIGHBOR_UPDATES {
+            let Some((pos, from)) = self.neighbor_updates.pop_front() else {
+                break;
+            };
+            if let Ok(state) = self.get_block(pos)
+                && !state.is_air()
+            {
+                registry
+                    .block_of(state)
+                    .behavior
+                    .get()
+                    .on_neighbor_changed(self, pos, state, from);
+            }
+        }
+    }
+
     pub fn load_chunk(&mut self, position: ChunkPosition) {
         if self.chunks.contains_key(&position) || self.in_flight.contains(&position) {
             return;
         }
 
-        self.job_tx.send(GenJob { position }).unwrap();
+        self.io.load(position);
         self.in_flight.insert(position);
     }
 
     pub fn unload_chunk(&mut self, position: ChunkPosition, ctx: &mut Context) {
-        if let Some(chunk) = self.get_chunk(&position) {
+        if let Some(mut chunk) = self.chunks.remove(&position) {
+            let column = position.column();
+            if let Some(heights) = self.columns.get_mut(&column) {
+                heights.remove(&position.y);
+                if heights.is_empty() {
+                    self.columns.remove(&column);
+                }
+            }
+
             if let Some(mesh) = chunk.mesh.as_ref() {
                 ctx.despawn_model(mesh);
             }
+            Self::save_chunk(&self.io, &mut chunk);
 
-            self.chunks.remove(&position);
             self.mark_neighbors_for_rebuild(&position);
         }
     }
 
+    /// Queues a chunk for saving if it changed since it was loaded or last saved.
+    fn save_chunk(io: &IoThread, chunk: &mut Chunk) {
+        if chunk.modified {
+            io.save(chunk.position, chunk.encode());
+            chunk.modified = false;
+        }
+    }
+
+    /// Queues every modified chunk and the metadata, with the current world time
+    /// and camera, for saving.
+    fn save(&mut self, ctx: &Context) {
+        for chunk in self.chunks.values_mut() {
+            Self::save_chunk(&self.io, chunk);
+        }
+
+        let camera = ctx.get_camera();
+        self.metadata.time = self.tick;
+        self.metadata.camera = CameraState {
+            position: camera.position.into(),
+            yaw: camera.yaw.0,
+            pitch: camera.pitch.0,
+        };
+        self.io.save_metadata(self.metadata.clone());
+    }
+
+    /// Marks the chunks around `position` for remeshing; their border faces and
+    /// occlusion depend on its blocks.
     fn mark_neighbors_for_rebuild(&mut self, position: &ChunkPosition) {
-        let neighbors = [
-            ChunkPosition::new(position.x - 1, position.z),
-            ChunkPosition::new(position.x + 1, position.z),
-            ChunkPosition::new(position.x, position.z - 1),
-            ChunkPosition::new(position.x, position.z + 1),
-        ];
-
-        for neighbor_pos in &neighbors {
-            if let Some(neighbor_chunk) = self.chunks.get_mut(neighbor_pos) {
+        for neighbor_pos in position.surrounding() {
+            if let Some(neighbor_chunk) = self.chunks.get_mut(&neighbor_pos) {
                 neighbor_chunk.need_rebuilt = true;
             }
         }
     }
 
     fn update_chunks_around_player(&mut self, player_pos: Point3<f32>, ctx: &mut Context) {
-        let player_chunk = ChunkPosition::from_world_pos(player_pos.x, player_pos.z);
+        let player_chunk = BlockPos::containing(player_pos).chunk();
 
         if self.last_player_chunk == Some(player_chunk) {
             return;
         }
         self.last_player_chunk = Some(player_chunk);
 
-        let mut should_be_loaded = HashSet::new();
         let rd = self.render_distance as i32;
-
+        let vrd = self.vertical_render_distance as i32;
         for dx in -rd..=rd {
             for dz in -rd..=rd {
-                if dx * dx + dz * dz <= rd * rd {
-                    should_be_loaded
-                        .insert(ChunkPosition::new(player_chunk.x + dx, player_chunk.z + dz));
+                for dy in -vrd..=vrd {
+                    let pos = ChunkPosition::new(
+                        player_chunk.x + dx,
+                        player_chunk.y + dy,
+                        player_chunk.z + dz,
+                    );
+                    if self.in_load_range(pos) {
+                        self.load_chunk(pos);
+                    }
                 }
             }
         }
 
-        for pos in &should_be_loaded {
-            if !self.chunks.contains_key(pos) || !self.in_flight.contains(pos) {
-                self.load_chunk(*pos);
-            }
-        }
-
         let chunks_to_unload: Vec<ChunkPosition> = self
             .chunks
             .keys()
-            .filter(|pos| !should_be_loaded.contains(pos))
+            .filter(|pos| !self.in_load_range(**pos))
             .cloned()
             .collect();
 
         for pos in chunks_to_unload {
             self.unload_chunk(pos, ctx);
         }
+
+        self.update_lods();
     }
 
-    fn flush_generated_chunks(&mut self, ctx: &mut Context) {
+    /// Whether a chunk at `position` is within the render distances of the
+    /// camera's chunk, or whether anything may load before the camera is known.
+    fn in_load_range(&self, position: ChunkPosition) -> bool {
+        let Some(center) = self.last_player_chunk else {
+            return true;
+        };
+        let (dx, dy, dz) = (
+            position.x - center.x,
+            position.y - center.y,
+            position.z - center.z,
+        );
+        let (rd, vrd) = (
+            self.render_distance as i32,
+            self.vertical_render_distance as i32,
+        );
+        dx * dx + dz * dz <= rd * rd && dy.abs() <= vrd
+    }
+
+    /// Level of detail for a chunk at `position`, from its horizontal distance to
+    /// the camera's chunk.
+    fn lod_at(&self, position: ChunkPosition) -> u8 {
+        let Some(center) = self.last_player_chunk else {
+            return 0;
+        };
+        let (dx, dz) = (position.x - center.x, position.z - center.z);
+        let distance_squared = (dx * dx + dz * dz) as u32;
+        self.lod_distances
+            .iter()
+            .take_while(|&&distance| distance_squared > distance * distance)
+            .count() as u8
+    }
+
+    /// Moves chunks to the level of detail of their distance to the camera. Their
+    /// neighbours are remeshed too, to cover the new seams.
+    fn update_lods(&mut self) {
+        let changed: Vec<(ChunkPosition, u8)> = self
+            .chunks
+            .values()
+            .filter_map(|chunk| {
+                let lod = self.lod_at(chunk.position);
+                (chunk.lod != lod).then_some((chunk.position, lod))
+            })
+            .collect();
+
+        for (position, lod) in changed {
+            if let Some(chunk) = self.chunks.get_mut(&position) {
+                chunk.lod = lod;
+                chunk.need_rebuilt = true;
+            }
+            self.mark_neighbors_for_rebuild(&position);
+        }
+    }
+
+    /// Adds generated chunks to the world; they are meshed by
+    /// [`World::dispatch_mesh_jobs`] once their neighbours are known.
+    fn flush_generated_chunks(&mut self) {
         let drained: Vec<Chunk> = self.result_rx.try_iter().collect();
 
         for mut chunk in drained {
-            let data = chunk.build_mesh(self);
-            chunk.upload_mesh(data, ctx);
-
-            ctx.spawn_model(chunk.mesh.as_ref().unwrap());
+            self.in_flight.remove(&chunk.position);
+            // The camera moved away while the chunk was generated.
+            if !self.in_load_range(chunk.position) {
+                continue;
+            }
 
+            chunk.lod = self.lod_at(chunk.position);
             self.mark_neighbors_for_rebuild(&chunk.position);
 
-            self.in_flight.remove(&chunk.position);
+            self.columns
+                .entry(chunk.position.column())
+                .or_default()
+                .insert(chunk.position.y);
             self.chunks.insert(chunk.position, chunk);
         }
     }
 
-    pub fn rebuild_chunk_meshes(&mut self, ctx: &mut Context) {
-        let chunks_to_rebuild: Vec<ChunkPosition> = self
-            .chunks
-            .iter()
-            .filter(|(_, chunk)| chunk.need_rebuilt)
-            .map(|(pos, _)| *pos)
-            .collect();
-
-        for pos in chunks_to_rebuild {
-            if let Some(chunk) = self.chunks.get_mut(&pos) {
-                if let Some(old_mesh) = chunk.mesh.as_ref() {
-                    ctx.despawn_model(old_mesh);
-                }
+    /// Starts meshing every chunk that needs a new mesh on the worker pool.
+    /// A chunk changed again before its mesh is done is simply meshed again;
+    /// the outdated mesh is dropped when it arrives.
+    fn dispatch_mesh_jobs(&mut self) {
+        let textures = self.block_textures().indices();
+        let mut generation = self.next_mesh_generation;
+        let mut jobs = Vec::new();
+        for chunk in self.chunks.values().filter(|chunk| chunk.need_rebuilt) {
+            generation += 1;
+
+            if chunk.is_hidden(self) {
+                let _ = self.mesh_tx.send(MeshResult {
+                    position: chunk.position,
+                    generation,
+                    meshes: Default::default(),
+                });
+            } else {
+                let snapshot = ChunkSnapshot::capture(chunk, self, textures.clone());
+                let mesh_tx = self.mesh_tx.clone();
+                rayon::spawn(move || {
+                    let _ = mesh_tx.send(MeshResult {
+                        position: snapshot.position,
+                        generation,
+                        meshes: snapshot.build_mesh(),
+                    });
+                });
             }
+            jobs.push((chunk.position, generation));
+        }
+        self.next_mesh_generation = generation;
 
-            if let Some(mut chunk) = self.chunks.remove(&pos) {
-                let data = chunk.build_mesh(self);
-                chunk.upload_mesh(data, ctx);
-                if let Some(new_mesh) = chunk.mesh.as_ref() {
-                    ctx.spawn_model(new_mesh);
-                }
+        for (position, generation) in jobs {
+            if let Some(chunk) = self.chunks.get_mut(&position) {
                 chunk.need_rebuilt = false;
+                chunk.mesh_generation = generation;
+            }
+        }
+    }
 
-                self.chunks.insert(pos, chunk);
+    /// Replaces the models of chunks whose meshes are done, at most
+    /// [`World::mesh_upload_budget`] per call.
+    fn upload_meshes(&mut self, ctx: &mut Context) {
+        let mut uploaded = 0;
+        while uploaded < self.mesh_upload_budget {
+            let Ok(result) = self.mesh_rx.try_recv() else {
+                break;
+            };
+            let textures = self
+                .textures
+                .as_ref()
+                .expect("Block textures are loaded when the world starts");
+            // The chunk was unloaded or changed since the job started.
+            let Some(chunk) = self
+                .chunks
+                .get_mut(&result.position)
+                .filter(|chunk| chunk.mesh_generation == result.generation)
+            else {
+                continue;
+            };
+
+            if let Some(old_mesh) = chunk.mesh.take() {
+                ctx.despawn_model(&old_mesh);
+            }
+            chunk.upload_mesh(result.meshes, textures, ctx);
+            if let Some(new_mesh) = chunk.mesh.as_ref() {
+                ctx.spawn_model(new_mesh);
             }
+            uploaded += 1;
         }
     }
 }
 
 impl Object for World {
     #![allow(unused_variables)]
+    fn start(&mut self, ctx: &mut Context) {
+        self.textures =
+            Some(BlockTextures::load(&self.registry, ctx).expect("Failed to load block textures"));
+    }
+
     fn update(&mut self, ctx: &mut Context, delta: f32) {
-        self.flush_generated_chunks(ctx);
+        self.tick_accumulator += delta;
+        while self.tick_accumulator >= 1.0 / TICKS_PER_SECOND {
+            self.tick_accumulator -= 1.0 / TICKS_PER_SECOND;
+            self.run_tick();
+        }
+
+        self.flush_generated_chunks();
         self.update_chunks_around_player(ctx.get_camera_position(), ctx);
-        self.rebuild_chunk_meshes(ctx);
+        self.dispatch_mesh_jobs();
+        self.upload_meshes(ctx);
+        let camera_position = ctx.get_camera_position();
+        for chunk in self.chunks.values_mut() {
+            chunk.sort_translucent(camera_position, ctx);
+        }
+
+        self.autosave_timer += delta;
+        if self.autosave_timer >= AUTOSAVE_INTERVAL {
+            self.autosave_timer = 0.0;
+            self.save(ctx);
+        }
+    }
+
+    fn destroy(&mut self, ctx: &mut Context) {
+        self.save(ctx);
+        self.io.flush();
     }
 }
@@ -0,0 +1,151 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use super::WorldGenerator;
+use crate::{
+    blocks::BlockStateId,
+    chunk::{CHUNK_SIZE, Chunk},
+    coords::{BlockPos, LocalPos},
+};
+
+/// How far above the surface generated features such as trees reach.
+pub(super) const TREE_CLEARANCE: i32 = 8;
+
+impl WorldGenerator {
+    /// Adds ore veins, plants and trees on top of the layered terrain of a chunk
+    /// whose column surfaces are at `heights`.
+    pub(super) fn place_features(
+        &self,
+        chunk: &mut Chunk,
+        heights: &[[i32; CHUNK_SIZE]; CHUNK_SIZE],
+    ) {
+        self.generate_ores(chunk);
+
+        let mut rng = self.column_rng(chunk);
+        self.generate_plants(chunk, heights, &mut rng);
+        self.generate_trees(chunk, heights, &mut rng);
+    }
+
+    /// Random numbers shared by every chunk of a column, so features spanning
+    /// chunks vertically come out the same in each of them.
+    fn column_rng(&self, chunk: &Chunk) -> StdRng {
+        let seed = ((self.get_seed() as u64) << 32)
+            ^ (chunk.position.x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (chunk.position.z as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+
+        StdRng::seed_from_u64(seed)
+    }
+
+    fn chunk_rng(&self, chunk: &Chunk) -> StdRng {
+        let seed = self.column_rng(chunk).random::<u64>()
+            ^ (chunk.position.y as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+
+        StdRng::seed_from_u64(seed)
+    }
+
+    /// Sets a block given its world position, if that position falls inside the chunk.
+    fn set_at(chunk: &mut Chunk, pos: BlockPos, block: BlockStateId) {
+        if pos.chunk() == chunk.position {
+            chunk.set_block(pos.local(), block);
+        }
+    }
+
+    fn get_at(chunk: &Chunk, pos: BlockPos) -> Option<BlockStateId> {
+        (pos.chunk() == chunk.position).then(|| chunk.get_block(pos.local()))
+    }
+
+    fn generate_ores(&self, chunk: &mut Chunk) {
+        let mut rng = self.chunk_rng(chunk);
+        let origin_y = chunk.position.origin().y;
+
+        for (ore, attempts, max_y, vein_size) in [
+            (self.blocks.coal_ore, 2, i32::MAX, 8),
+            (self.blocks.iron_ore, 2, 64, 5),
+        ] {
+            for _ in 0..attempts {
+                let mut x = rng.random_range(0..CHUNK_SIZE);
+                let mut y = rng.random_range(0..CHUNK_SIZE);
+                let mut z = rng.random_range(0..CHUNK_SIZE);
+                if origin_y + y as i32 >= max_y {
+                    continue;
+                }
+
+                for _ in 0..vein_size {
+                    let pos = LocalPos::new(x, y, z);
+                    if chunk.get_block(pos) == self.blocks.stone {
+                        chunk.set_block(pos, ore);
+                    }
+
+                    match rng.random_range(0..3) {
+                        0 => x = (x + 1).min(CHUNK_SIZE - 1),
+                        1 => y = y.saturating_sub(1),
+                        _ => z = (z + 1).min(CHUNK_SIZE - 1),
+                    }
+                }
+            }
+        }
+    }
+
+    fn generate_trees(
+        &self,
+        chunk: &mut Chunk,
+        heights: &[[i32; CHUNK_SIZE]; CHUNK_SIZE],
+        rng: &mut StdRng,
+    ) {
+        let origin = chunk.position.origin();
+        for _ in 0..3 {
+            // Keep the canopy inside the column so trees never need their horizontal neighbours.
+            let x = rng.random_range(2..CHUNK_SIZE - 2);
+            let z = rng.random_range(2..CHUNK_SIZE - 2);
+            let ground = heights[x][z] - 1;
+
+            if self.surface_blocks(ground).0 != self.blocks.grass {
+                continue;
+            }
+
+            let trunk_height = rng.random_range(4..7);
+            let top = ground + trunk_height;
+            let base = BlockPos::new(origin.x + x as i32, ground, origin.z + z as i32);
+
+            for y in (top - 2)..=(top + 1) {
+                let radius: i32 = if y + 1 >= top { 1 } else { 2 };
+                for dx in -radius..=radius {
+                    for dz in -radius..=radius {
+                        let corner = dx.abs() == radius && dz.abs() == radius;
+                        if corner && (radius == 1 || rng.random_bool(0.5)) {
+                            continue;
+                        }
+
+                        let pos = base.offset(dx, y - ground, dz);
+                        if Self::get_at(chunk, pos) == Some(BlockStateId::AIR) {
+                            Self::set_at(chunk, pos, self.blocks.oak_leaves);
+                        }
+                    }
+                }
+            }
+
+            Self::set_at(chunk, base, self.blocks.dirt);
+            for dy in 1..=trunk_height {
+                Self::set_at(chunk, base.offset(0, dy, 0), self.blocks.oak_log);
+            }
+        }
+    }
+
+    fn generate_plants(
+        &self,
+        chunk: &mut Chunk,
+        heights: &[[i32; CHUNK_SIZE]; CHUNK_SIZE],
+        rng: &mut StdRng,
+    ) {
+        let origin = chunk.position.origin();
+        for _ in 0..24 {
+            let x = rng.random_range(0..CHUNK_SIZE);
+            let z = rng.random_range(0..CHUNK_SIZE);
+            let ground = heights[x][z] - 1;
+
+            if self.surface_blocks(ground).0 == self.blocks.grass {
+                let pos = BlockPos::new(origin.x + x as i32, ground + 1, origin.z + z as i32);
+                Self::set_at(chunk, pos, self.blocks.short_grass);
+            }
+        }
+    }
+}
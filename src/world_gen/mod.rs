@@ -0,0 +1,173 @@
+mod features;
+
+use noise::{NoiseFn, Perlin, Seedable};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blocks::{BlockRegistry, BlockStateId},
+    chunk::{CHUNK_SIZE, Chunk},
+    coords::LocalPos,
+};
+
+/// Block states the generator places, resolved from the registry once.
+struct TerrainBlocks {
+    stone: BlockStateId,
+    dirt: BlockStateId,
+    grass: BlockStateId,
+    sand: BlockStateId,
+    gravel: BlockStateId,
+    water: BlockStateId,
+    oak_log: BlockStateId,
+    oak_leaves: BlockStateId,
+    coal_ore: BlockStateId,
+    iron_ore: BlockStateId,
+    snow: BlockStateId,
+    short_grass: BlockStateId,
+}
+
+impl TerrainBlocks {
+    fn new(registry: &BlockRegistry) -> anyhow::Result<Self> {
+        let state = |name: &str| -> anyhow::Result<BlockStateId> {
+            Ok(registry.get_by_name(name)?.default_state())
+        };
+
+        Ok(Self {
+            stone: state("stone")?,
+            dirt: state("dirt")?,
+            grass: state("grass")?,
+            sand: state("sand")?,
+            gravel: state("gravel")?,
+            water: state("water")?,
+            oak_log: state("oak_log")?,
+            oak_leaves: state("oak_leaves")?,
+            coal_ore: state("coal_ore")?,
+            iron_ore: state("iron_ore")?,
+            snow: state("snow")?,
+            short_grass: state("short_grass")?,
+        })
+    }
+}
+
+/// Tunable terrain parameters, saved with the world so it generates the same way
+/// when reopened.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneratorSettings {
+    /// Height difference between the lowest and highest terrain.
+    pub amplitude: f32,
+    /// Height of the lowest terrain.
+    pub base_height: f32,
+    /// Horizontal scale of the height noise; larger values give rougher terrain.
+    pub frequency: f32,
+    pub sea_level: i32,
+    /// Surfaces at or above this height are covered in snow.
+    pub snow_line: i32,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            amplitude: 32.0,
+            base_height: 64.0,
+            frequency: 0.03,
+            sea_level: 76,
+            snow_line: 88,
+        }
+    }
+}
+
+pub struct WorldGenerator {
+    perlin: Perlin,
+    settings: GeneratorSettings,
+    blocks: TerrainBlocks,
+}
+
+impl WorldGenerator {
+    pub fn new(
+        seed: u32,
+        settings: GeneratorSettings,
+        registry: &BlockRegistry,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            perlin: Perlin::new(seed),
+            settings,
+            blocks: TerrainBlocks::new(registry)?,
+        })
+    }
+
+    pub fn get_seed(&self) -> u32 {
+        self.perlin.seed()
+    }
+
+    /// World height one above the surface block of the column at `world_x`, `world_z`.
+    pub fn height_at(&self, world_x: i32, world_z: i32) -> i32 {
+        let n = (self.perlin.get([
+            world_x as f64 * self.settings.frequency as f64,
+            world_z as f64 * self.settings.frequency as f64,
+        ]) + 1.0)
+            * 0.5;
+
+        (n * self.settings.amplitude as f64 + self.settings.base_height as f64).round() as i32
+    }
+
+    /// The top and filler blocks of a column whose surface block is at `surface`.
+    fn surface_blocks(&self, surface: i32) -> (BlockStateId, BlockStateId) {
+        let blocks = &self.blocks;
+        let underwater = surface + 1 < self.settings.sea_level;
+        let beach = !underwater && surface <= self.settings.sea_level + 1;
+
+        if underwater && surface + 4 < self.settings.sea_level {
+            (blocks.gravel, blocks.sand)
+        } else if underwater || beach {
+            (blocks.sand, blocks.sand)
+        } else if surface >= self.settings.snow_line {
+            (blocks.snow, blocks.dirt)
+        } else {
+            (blocks.grass, blocks.dirt)
+        }
+    }
+
+    pub fn generate_chunk(&self, chunk: &mut Chunk) {
+        let origin = chunk.position.origin();
+        let heights: [[i32; CHUNK_SIZE]; CHUNK_SIZE] = std::array::from_fn(|x| {
+            std::array::from_fn(|z| self.height_at(origin.x + x as i32, origin.z + z as i32))
+        });
+
+        let highest = heights.iter().flatten().copied().max().unwrap_or(0);
+        if origin.y > highest.max(self.settings.sea_level) + features::TREE_CLEARANCE {
+            return;
+        }
+
+        for (x, column) in heights.iter().enumerate() {
+            for (z, &column_height) in column.iter().enumerate() {
+                self.generate_column(chunk, x, z, column_height);
+            }
+        }
+
+        self.place_features(chunk, &heights);
+    }
+
+    fn generate_column(&self, chunk: &mut Chunk, x: usize, z: usize, height: i32) {
+        let surface = height - 1;
+        let (top, filler) = self.surface_blocks(surface);
+
+        // Everything above the surface and the sea is left as air.
+        for pos in LocalPos::column(x, z) {
+            let world_y = chunk.position.block_pos(pos).y;
+            let block = if world_y == surface {
+                top
+            } else if world_y < surface {
+                if world_y + 4 > surface {
+                    filler
+                } else {
+                    self.blocks.stone
+                }
+            } else if world_y < self.settings.sea_level {
+                self.blocks.water
+            } else {
+                continue;
+            };
+            chunk.set_block(pos, block);
+        }
+    }
+}
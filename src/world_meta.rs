@@ -0,0 +1,61 @@
+use std::{fs, path::Path};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{coords::BlockPos, io_thread::write_atomic, world_gen::GeneratorSettings};
+
+/// Version of the world directory layout; worlds written by newer versions are refused.
+pub const WORLD_FORMAT_VERSION: u32 = 1;
+
+/// File in the world directory holding its [`WorldMetadata`].
+pub const METADATA_FILE: &str = "level.ron";
+
+/// Where the camera was when the world was last saved.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraState {
+    pub position: [f32; 3],
+    /// In radians.
+    pub yaw: f32,
+    /// In radians.
+    pub pitch: f32,
+}
+
+/// Everything about a world that is not stored in its chunks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorldMetadata {
+    pub format_version: u32,
+    pub seed: u32,
+    pub generator: GeneratorSettings,
+    pub spawn: BlockPos,
+    pub camera: CameraState,
+    /// World ticks elapsed since the world was created.
+    pub time: u64,
+}
+
+impl WorldMetadata {
+    pub fn read(dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join(METADATA_FILE);
+        let source = fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let metadata: Self = ron::from_str(&source)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+
+        if metadata.format_version > WORLD_FORMAT_VERSION {
+            return Err(anyhow!(
+                "{} uses world format version {}, but only up to {} is supported",
+                dir.display(),
+                metadata.format_version,
+                WORLD_FORMAT_VERSION
+            ));
+        }
+        Ok(metadata)
+    }
+
+    pub fn write(&self, dir: &Path) -> anyhow::Result<()> {
+        let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::create_dir_all(dir)?;
+        write_atomic(&dir.join(METADATA_FILE), source.as_bytes())?;
+        Ok(())
+    }
+}